@@ -10,6 +10,7 @@ import List from "./list";
 import { ErrorModal, useError } from "./error_modal";
 import { PlaylistDetails } from "../services/api/playlist";
 import { EntryBrief, EntryDetails } from "../services/api/entry";
+import { SortKey } from "../utils/meta";
 
 interface Props {
   apiServices: ApiServices;
@@ -74,11 +75,8 @@ export default function Entry(props: Props) {
     props.fetchEntries(props.group.id);
   };
 
-  const sortEntries = async (values: { [key: string]: any }) => {
-    await props.apiServices.entry.sort({
-      by: values["by"].value,
-      ascend: values["ascend"],
-    });
+  const sortEntries = async (keys: SortKey[]) => {
+    await props.apiServices.entry.sort({ keys, group_id: props.group.id });
     props.fetchEntries(props.group.id);
   };
 
@@ -9,6 +9,7 @@ import { ErrorModal, useError } from "./error_modal";
 import { ScanModal, useScan } from "./scan_modal";
 import { PlaylistDetails } from "../services/api/playlist";
 import { GroupBrief, GroupDetails } from "../services/api/group";
+import { SortKey } from "../utils/meta";
 
 interface Props {
   apiServices: ApiServices;
@@ -65,10 +66,10 @@ export default function Group(props: Props) {
     props.fetchGroups(props.playlist.id);
   };
 
-  const sortGroups = async (values: { [key: string]: any }) => {
+  const sortGroups = async (keys: SortKey[]) => {
     await props.apiServices.group.sort({
-      by: values["by"].value,
-      ascend: values["ascend"],
+      keys,
+      playlist_id: props.playlist.id,
     });
     props.fetchGroups(props.playlist.id);
   };
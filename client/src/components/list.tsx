@@ -7,7 +7,14 @@ import {
   ArrowDownCircle,
   SortUp,
 } from "react-bootstrap-icons";
-import { MetaCmpBy } from "../utils/meta";
+import {
+  MetaCmpBy,
+  SortKey,
+  randomSeed,
+  sortBy,
+  sortByKind,
+  sortBySeed,
+} from "../utils/meta";
 import { FormModal, useForm } from "./form_modal";
 import { useTranslation } from "react-i18next";
 import {
@@ -32,7 +39,7 @@ interface Props {
   handleDelete: (id: string) => Promise<void>;
   handleSelect: (id: string) => void;
 
-  handleSort?: (values: { [key: string]: any }) => Promise<void>;
+  handleSort?: (keys: SortKey[]) => Promise<void>;
   handleShift?: (id: string, offset: number) => Promise<void>;
 }
 
@@ -58,32 +65,121 @@ export default function List(props: Props) {
   const errorState = useError();
   const sortFormState = useForm();
 
+  const sortByOptions = [
+    { value: MetaCmpBy.Default, label: t("sort.by.default") },
+    { value: MetaCmpBy.Path, label: t("sort.by.path") },
+    { value: MetaCmpBy.CreatedAt, label: t("sort.by.created_at") },
+    { value: MetaCmpBy.UpdatedAt, label: t("sort.by.updated_at") },
+    { value: MetaCmpBy.Duration, label: t("sort.by.duration") },
+    { value: MetaCmpBy.Size, label: t("sort.by.size") },
+    { value: MetaCmpBy.Width, label: t("sort.by.width") },
+    { value: MetaCmpBy.Height, label: t("sort.by.height") },
+    { value: MetaCmpBy.AspectRatio, label: t("sort.by.aspect_ratio") },
+    { value: MetaCmpBy.NaturalPath, label: t("sort.by.natural_path") },
+    { value: MetaCmpBy.FileName, label: t("sort.by.file_name") },
+    { value: MetaCmpBy.TakenAt, label: t("sort.by.taken_at") },
+    { value: MetaCmpBy.Shuffle, label: t("sort.by.shuffle") },
+  ];
+
+  // The last submitted keys, seed included, so that shuffles can be repeated.
+  const storedSortKeys = (): SortKey[] => {
+    try {
+      return JSON.parse(localStorage.getItem("sort_keys") || "[]");
+    } catch {
+      return [];
+    }
+  };
+
   const popupSortModal = () => {
+    const [first, then]: (SortKey | undefined)[] = storedSortKeys();
+    const option = (key?: SortKey) =>
+      sortByOptions.find(
+        (option) => key && option.value === sortByKind(key.by),
+      );
+    const seed =
+      [first, then]
+        .map((key) => (key ? sortBySeed(key.by) : null))
+        .find((seed) => seed !== null) ?? randomSeed();
     sortFormState.popup({
       header: t("sort.title"),
       rows: [
         {
           name: "by",
           type: "select",
-          initial: { value: MetaCmpBy.Default, label: t("sort.by.default") },
+          initial: option(first) ?? {
+            value: MetaCmpBy.Default,
+            label: t("sort.by.default"),
+          },
           label: t("sort.by.label"),
+          options: sortByOptions,
+        },
+        {
+          name: "ascend",
+          type: "checkbox",
+          initial: first?.ascend ?? true,
+          label: t("sort.ascend.label"),
+        },
+        {
+          name: "then_by",
+          type: "select",
+          initial: option(then) ?? {
+            value: null,
+            label: t("sort.then_by.none"),
+          },
+          label: t("sort.then_by.label"),
           options: [
-            { value: MetaCmpBy.Default, label: t("sort.by.default") },
-            { value: MetaCmpBy.Path, label: t("sort.by.path") },
-            { value: MetaCmpBy.CreatedAt, label: t("sort.by.created_at") },
-            { value: MetaCmpBy.UpdatedAt, label: t("sort.by.updated_at") },
+            { value: null, label: t("sort.then_by.none") },
+            ...sortByOptions,
           ],
         },
         {
-          name: "ascend",
+          name: "then_ascend",
           type: "checkbox",
-          initial: true,
+          initial: then?.ascend ?? true,
           label: t("sort.ascend.label"),
         },
+        {
+          name: "ignore_case",
+          type: "checkbox",
+          initial: first?.ignore_case ?? false,
+          label: t("sort.ignore_case.label"),
+        },
+        {
+          name: "seed",
+          type: "number",
+          initial: seed,
+          label: t("sort.seed.label"),
+          validator: (value: number | string) => {
+            const seed = Number(value);
+            return Number.isSafeInteger(seed) && seed >= 0
+              ? null
+              : t("sort.errors.invalid_seed");
+          },
+        },
       ],
     });
   };
 
+  const handleSortSubmit = async (values: { [key: string]: any }) => {
+    const seed = Number(values["seed"]);
+    const keys: SortKey[] = [
+      {
+        by: sortBy(values["by"].value, seed),
+        ascend: values["ascend"],
+        ignore_case: values["ignore_case"],
+      },
+    ];
+    if (values["then_by"].value) {
+      keys.push({
+        by: sortBy(values["then_by"].value, seed),
+        ascend: values["then_ascend"],
+        ignore_case: values["ignore_case"],
+      });
+    }
+    localStorage.setItem("sort_keys", JSON.stringify(keys));
+    await props.handleSort?.(keys);
+  };
+
   const columnHelper = createColumnHelper<{ [key: string]: any }>();
   const columns = Object.entries(props.headers)
     .map(([k, v]) => {
@@ -176,7 +272,7 @@ export default function List(props: Props) {
     <Stack gap={2}>
       <ErrorModal state={errorState} />
       {props.handleSort && (
-        <FormModal state={sortFormState} handleSubmit={props.handleSort} />
+        <FormModal state={sortFormState} handleSubmit={handleSortSubmit} />
       )}
       <Table striped bordered hover>
         <thead>
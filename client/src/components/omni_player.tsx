@@ -219,6 +219,20 @@ export function OmniPlayer(props: Props) {
               controls
               className="w-100 h-100"
               onEnded={() => setCanProceed(true)}
+              config={{
+                file: {
+                  attributes: {
+                    poster: `/stream/entries/${props.entry.id}/artwork`,
+                  },
+                  tracks: props.entry.subtitles.map((subtitle, index) => ({
+                    kind: "subtitles",
+                    src: `/stream/entries/${props.entry.id}/subtitles/${encodeURIComponent(subtitle.lang)}`,
+                    srcLang: subtitle.lang,
+                    label: subtitle.lang,
+                    default: index === 0,
+                  })),
+                },
+              }}
             />
           )}
         </Col>
@@ -1,8 +1,9 @@
-import { Dispatch, SetStateAction, useState } from "react";
+import { Dispatch, SetStateAction, useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import {
   Button,
   Col,
+  Form,
   FormControl,
   Modal,
   Row,
@@ -12,6 +13,7 @@ import {
 import { PlusCircle, XCircle } from "react-bootstrap-icons";
 
 import { ApiServices } from "../services/api";
+import { ScanJobStatus, ScanTree } from "../services/api/scanner";
 import { ErrorModal, useError } from "./error_modal";
 
 interface ScanState {
@@ -30,6 +32,23 @@ export function useScan(): ScanState {
   return { show, setShow, popup };
 }
 
+function flattenTree(tree: ScanTree, depth = 0): [ScanTree, number][] {
+  return [[tree, depth] as [ScanTree, number]].concat(
+    ...tree.children.map((child) => flattenTree(child, depth + 1)),
+  );
+}
+
+function formatSize(bytes: number): string {
+  const units = ["B", "KiB", "MiB", "GiB", "TiB"];
+  let size = bytes;
+  let unit = 0;
+  while (size >= 1024 && unit < units.length - 1) {
+    size /= 1024;
+    unit += 1;
+  }
+  return `${unit === 0 ? size : size.toFixed(1)} ${units[unit]}`;
+}
+
 interface Props {
   state: ScanState;
   apiServices: ApiServices;
@@ -41,6 +60,9 @@ interface Props {
 
 export function ScanModal(props: Props) {
   const [rootPath, setRootPath] = useState<string | null>(null);
+  const [exclude, setExclude] = useState(".thumbnails,@eaDir,.git");
+  const [skipHidden, setSkipHidden] = useState(false);
+  const [followSymlinks, setFollowSymlinks] = useState(false);
   const [inputMime, setInputMime] = useState<string>("");
   const [allowedMimes, setAllowedMimes] = useState<string[]>([]);
   const [ungroupedPaths, setUngroupedPaths] = useState<string[]>([]);
@@ -48,6 +70,9 @@ export function ScanModal(props: Props) {
   const [groupedPaths, setGroupedPaths] = useState<{
     [key: string]: string[];
   }>({});
+  const [job, setJob] = useState<ScanJobStatus | null>(null);
+  const [tree, setTree] = useState<ScanTree | null>(null);
+  const [checkedDirs, setCheckedDirs] = useState<string[]>([]);
 
   const { t } = useTranslation();
 
@@ -71,6 +96,8 @@ export function ScanModal(props: Props) {
     setUngroupedPaths([]);
     setGroupPath("");
     setGroupedPaths({});
+    setTree(null);
+    setCheckedDirs([]);
   };
 
   const addAllowedMime = () => {
@@ -96,11 +123,108 @@ export function ScanModal(props: Props) {
       return errorState.popup(t("scan.errors.allowed_mimes_not_set"));
     }
     props.apiServices.scanner
-      .index({ root_path: rootPath, allowed_mimes: allowedMimes.join(",") })
-      .then((resp) => setUngroupedPaths(resp))
+      .createJob({
+        root_path: rootPath,
+        allowed_mimes: allowedMimes,
+        exclude: exclude
+          .split(",")
+          .map((glob) => glob.trim())
+          .filter((glob) => glob.length > 0),
+        skip_hidden: skipHidden,
+        follow_symlinks: followSymlinks,
+      })
+      .then((created) => setJob(created))
       .catch((err) => errorState.popup(err));
   };
 
+  const handleStatus = (status: ScanJobStatus) => {
+    if (status.state === "running") {
+      setJob(status);
+    } else if (status.state === "completed") {
+      Promise.all([
+        fetchPaths(status.id),
+        props.apiServices.scanner.jobTree(status.id),
+      ])
+        .then(([paths, tree]) => {
+          setUngroupedPaths(paths);
+          setTree(tree);
+        })
+        .then(() => props.apiServices.scanner.destroyJob(status.id))
+        .catch((err) => errorState.popup(err))
+        .finally(() => setJob(null));
+    } else {
+      setJob(null);
+    }
+  };
+
+  const jobId = job?.id;
+
+  useEffect(() => {
+    if (!jobId) {
+      return;
+    }
+    return props.apiServices.scanner.watchJob(jobId, handleStatus, () => {
+      errorState.popup(t("scan.errors.watch_failed"));
+      setJob(null);
+    });
+  }, [jobId]);
+
+  const fetchPaths = async (id: string) => {
+    let paths: string[] = [];
+    for (;;) {
+      const page = await props.apiServices.scanner.jobPaths(id, {
+        offset: paths.length,
+      });
+      paths = paths.concat(page.paths);
+      if (page.paths.length === 0 || paths.length >= page.total) {
+        return paths;
+      }
+    }
+  };
+
+  const cancel = () => {
+    if (job) {
+      props.apiServices.scanner
+        .destroyJob(job.id)
+        .catch((err) => errorState.popup(err));
+      setJob(null);
+    }
+  };
+
+  const toggleDir = (path: string, checked: boolean) => {
+    setCheckedDirs(
+      checked
+        ? checkedDirs.concat([path])
+        : checkedDirs.filter((checkedDir) => checkedDir !== path),
+    );
+  };
+
+  const groupCheckedDirs = () => {
+    if (!tree) {
+      return;
+    }
+    const moved = new Set<string>();
+    const clonedGroupedPaths: { [key: string]: string[] } = JSON.parse(
+      JSON.stringify(groupedPaths),
+    );
+    for (const [dir] of flattenTree(tree)) {
+      if (!checkedDirs.includes(dir.path)) {
+        continue;
+      }
+      const entries = dir.files.filter((file) => ungroupedPaths.includes(file));
+      if (entries.length === 0) {
+        continue;
+      }
+      clonedGroupedPaths[dir.path] = (
+        clonedGroupedPaths[dir.path] ?? []
+      ).concat(entries);
+      entries.forEach((entry) => moved.add(entry));
+    }
+    setUngroupedPaths(ungroupedPaths.filter((path) => !moved.has(path)));
+    setGroupedPaths(clonedGroupedPaths);
+    setCheckedDirs([]);
+  };
+
   const grouping = () => {
     const groupsToMatch = new Set<string>();
     let endingDelimiter = null;
@@ -208,6 +332,33 @@ export function ScanModal(props: Props) {
                   />
                 </Col>
               </Row>
+              <Row>
+                <Col md={3}>
+                  <span>{t("scan.exclude.label")}</span>
+                </Col>
+                <Col md={9}>
+                  <FormControl
+                    type="text"
+                    className="w-100"
+                    value={exclude}
+                    onChange={(e) => setExclude(e.target.value)}
+                  />
+                </Col>
+              </Row>
+              <Stack direction="horizontal" gap={3}>
+                <Form.Check
+                  type="checkbox"
+                  label={t("scan.skip_hidden.label")}
+                  checked={skipHidden}
+                  onChange={(e) => setSkipHidden(e.target.checked)}
+                />
+                <Form.Check
+                  type="checkbox"
+                  label={t("scan.follow_symlinks.label")}
+                  checked={followSymlinks}
+                  onChange={(e) => setFollowSymlinks(e.target.checked)}
+                />
+              </Stack>
               <Stack gap={2}>
                 <span>{t("scan.allowed_mimes.label")}</span>
                 <Table striped bordered hover>
@@ -264,6 +415,53 @@ export function ScanModal(props: Props) {
             </Stack>
           ) : (
             <Stack gap={3}>
+              {tree ? (
+                <Stack gap={2}>
+                  <Table striped bordered hover>
+                    <thead>
+                      <tr>
+                        <th style={{ whiteSpace: "nowrap", width: "1px" }}></th>
+                        <th>{t("scan.directories.title")}</th>
+                        <th>{t("scan.directories.files")}</th>
+                        <th>{t("scan.directories.total_files")}</th>
+                        <th>{t("scan.directories.total_size")}</th>
+                      </tr>
+                    </thead>
+                    <tbody>
+                      {flattenTree(tree).map(([dir, depth]) => {
+                        return (
+                          <tr key={dir.path}>
+                            <td>
+                              <Form.Check
+                                type="checkbox"
+                                disabled={dir.files.length === 0}
+                                checked={checkedDirs.includes(dir.path)}
+                                onChange={(e) =>
+                                  toggleDir(dir.path, e.target.checked)
+                                }
+                              />
+                            </td>
+                            <td style={{ paddingLeft: `${depth + 0.5}rem` }}>
+                              {dir.path}
+                            </td>
+                            <td>{dir.files.length}</td>
+                            <td>{dir.total_files}</td>
+                            <td>{formatSize(dir.total_size)}</td>
+                          </tr>
+                        );
+                      })}
+                    </tbody>
+                  </Table>
+                  <Button
+                    variant="info"
+                    className="align-self-end"
+                    disabled={checkedDirs.length === 0}
+                    onClick={groupCheckedDirs}
+                  >
+                    {t("scan.directories.group_checked")}
+                  </Button>
+                </Stack>
+              ) : null}
               <Stack direction="horizontal" gap={2}>
                 <FormControl
                   type="text"
@@ -325,7 +523,20 @@ export function ScanModal(props: Props) {
           )}
         </Modal.Body>
         <Modal.Footer>
-          {step() === 1 ? (
+          {step() === 1 && job ? (
+            <>
+              <span className="me-auto text-truncate">
+                {t("scan.progress", {
+                  dirs: job.dirs_visited,
+                  files: job.files_matched,
+                })}{" "}
+                {job.current_path}
+              </span>
+              <Button variant="secondary" onClick={cancel}>
+                {t("scan.cancel")}
+              </Button>
+            </>
+          ) : step() === 1 ? (
             <Button variant="primary" onClick={scan}>
               {t("scan.scan")}
             </Button>
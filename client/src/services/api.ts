@@ -1,4 +1,4 @@
-import { MetaCmpBy } from "../utils/meta";
+import { SortKey } from "../utils/meta";
 import {
   AppConfigService,
   instantiateAppConfigService,
@@ -51,8 +51,9 @@ export async function sendRequestJson<T>(
 }
 
 export interface SortArgs {
-  by: MetaCmpBy;
-  ascend: boolean;
+  keys: SortKey[];
+  group_id?: string;
+  playlist_id?: string;
 }
 
 export class ApiServices {
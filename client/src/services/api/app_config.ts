@@ -6,6 +6,9 @@ export interface AppConfigImmutable {}
 
 export interface AppConfigMutable {
   locale: string;
+  range_chunk_size: number | null;
+  read_ahead_size: number | null;
+  auto_orient: boolean;
 }
 
 export interface AppConfigBrief extends AppConfigImmutable, AppConfigMutable {}
@@ -4,10 +4,20 @@ import Remote from "./entry/remote";
 
 export const basePath = ["entries"];
 
+export interface Subtitle {
+  lang: string;
+  path: string;
+  format: "vtt" | "srt" | "ass";
+}
+
+export type MimeSource = "extension" | "confirmed" | "content";
+
 export interface EntryImmutable {
   id: string;
   mime: string;
+  mime_source: MimeSource;
   meta: Meta;
+  subtitles: Subtitle[];
   group_id: string;
 }
 
@@ -19,6 +29,8 @@ export interface EntryDetails extends EntryImmutable, EntryMutable {}
 
 export interface IndexArgs {
   group_id: string | null;
+  taken_from?: string;
+  taken_until?: string;
 }
 
 export interface CreateArgs {
@@ -1,14 +1,78 @@
 import Remote from "./scanner/remote";
 
 export const basePath = ["scanner"];
+export const jobsPath = ["scan_jobs"];
+
+export type ScanMode = "list" | "tree";
 
 export interface IndexArgs {
   root_path: string;
   allowed_mimes: string;
+  mode?: ScanMode;
+}
+
+export interface ScanOptions {
+  include?: string[];
+  exclude?: string[];
+  min_depth?: number;
+  max_depth?: number;
+  skip_hidden?: boolean;
+  follow_symlinks?: boolean;
+  min_size?: number;
+  max_size?: number;
+  modified_since?: string;
+}
+
+export interface CreateJobArgs extends ScanOptions {
+  root_path: string;
+  allowed_mimes: string[];
+}
+
+export type ScanJobState = "running" | "completed" | "cancelled" | "failed";
+
+export interface ScanJobStatus {
+  id: string;
+  state: ScanJobState;
+  dirs_visited: number;
+  files_matched: number;
+  current_path: string | null;
+}
+
+export interface PathsArgs {
+  offset: number;
+  limit?: number;
+}
+
+export interface PathsPage {
+  paths: string[];
+  total: number;
+}
+
+export interface ScanTree {
+  path: string;
+  files: string[];
+  size: number;
+  total_files: number;
+  total_size: number;
+  children: ScanTree[];
 }
 
 export interface ScannerService {
   index(args: IndexArgs): Promise<string[]>;
+
+  createJob(args: CreateJobArgs): Promise<ScanJobStatus>;
+
+  watchJob(
+    id: string,
+    handleStatus: (status: ScanJobStatus) => void,
+    handleError: () => void,
+  ): () => void;
+
+  jobPaths(id: string, args: PathsArgs): Promise<PathsPage>;
+
+  jobTree(id: string): Promise<ScanTree>;
+
+  destroyJob(id: string): Promise<void>;
 }
 
 export function instantiateScannerService(): ScannerService {
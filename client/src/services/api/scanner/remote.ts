@@ -1,7 +1,17 @@
 import qs from "qs";
 
-import { sendRequestJson } from "../../api";
-import { IndexArgs, ScannerService, basePath } from "../scanner";
+import { sendRequestJson, sendRequestVoid } from "../../api";
+import {
+  CreateJobArgs,
+  IndexArgs,
+  PathsArgs,
+  PathsPage,
+  ScanJobStatus,
+  ScanTree,
+  ScannerService,
+  basePath,
+  jobsPath,
+} from "../scanner";
 
 export default class Remote implements ScannerService {
   index(args: IndexArgs): Promise<string[]> {
@@ -9,4 +19,44 @@ export default class Remote implements ScannerService {
       query: qs.stringify(args),
     });
   }
+
+  createJob(args: CreateJobArgs): Promise<ScanJobStatus> {
+    return sendRequestJson("POST", jobsPath, { body: args });
+  }
+
+  watchJob(
+    id: string,
+    handleStatus: (status: ScanJobStatus) => void,
+    handleError: () => void,
+  ): () => void {
+    const source = new EventSource(
+      `/api/${jobsPath.concat([id, "events"]).join("/")}`,
+    );
+    source.onmessage = (event) => {
+      const status: ScanJobStatus = JSON.parse(event.data);
+      handleStatus(status);
+      if (status.state !== "running") {
+        source.close();
+      }
+    };
+    source.onerror = () => {
+      source.close();
+      handleError();
+    };
+    return () => source.close();
+  }
+
+  jobPaths(id: string, args: PathsArgs): Promise<PathsPage> {
+    return sendRequestJson("GET", jobsPath.concat([id, "paths"]), {
+      query: qs.stringify(args),
+    });
+  }
+
+  jobTree(id: string): Promise<ScanTree> {
+    return sendRequestJson("GET", jobsPath.concat([id, "tree"]));
+  }
+
+  destroyJob(id: string): Promise<void> {
+    return sendRequestVoid("DELETE", jobsPath.concat([id]));
+  }
 }
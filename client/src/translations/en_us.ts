@@ -21,10 +21,32 @@ export default {
       path: "Path",
       created_at: "Created at",
       updated_at: "Updated at",
+      duration: "Duration",
+      size: "Size",
+      width: "Width",
+      height: "Height",
+      aspect_ratio: "Aspect ratio",
+      natural_path: "Path (natural order)",
+      file_name: "File name",
+      taken_at: "Taken at",
+      shuffle: "Shuffle",
+    },
+    then_by: {
+      label: "Then by",
+      none: "None",
     },
     ascend: {
       label: "Ascend?",
     },
+    ignore_case: {
+      label: "Ignore case?",
+    },
+    seed: {
+      label: "Shuffle seed",
+    },
+    errors: {
+      invalid_seed: "Seed must be a non-negative integer",
+    },
   },
   list: {
     pagination: {
@@ -36,13 +58,30 @@ export default {
     errors: {
       root_not_set: "Please select a root path",
       allowed_mimes_not_set: "Please choose at least one mime type",
+      watch_failed: "Lost track of the scan",
     },
     root_path: {
       label: "Root path",
     },
+    exclude: {
+      label: "Excluded globs",
+    },
+    skip_hidden: {
+      label: "Skip hidden files",
+    },
+    follow_symlinks: {
+      label: "Follow symbolic links",
+    },
     allowed_mimes: {
       label: "Allowed MIMEs",
     },
+    directories: {
+      title: "Directories",
+      files: "Files",
+      total_files: "Files below",
+      total_size: "Size below",
+      group_checked: "Group checked directories",
+    },
     ungrouped: {
       title: "Ungrouped",
     },
@@ -51,6 +90,8 @@ export default {
       entry_count: "Entry count",
     },
     scan: "Scan",
+    progress: "{{dirs}} directories, {{files}} files",
+    cancel: "Cancel",
     back: "Back",
     submit: "Submit",
   },
@@ -21,10 +21,32 @@ export default {
       path: "パス",
       created_at: "新規日時",
       updated_at: "更新日時",
+      duration: "再生時間",
+      size: "サイズ",
+      width: "幅",
+      height: "高さ",
+      aspect_ratio: "アスペクト比",
+      natural_path: "パス（自然順）",
+      file_name: "ファイル名",
+      taken_at: "撮影日時",
+      shuffle: "シャッフル",
+    },
+    then_by: {
+      label: "次の基準",
+      none: "なし",
     },
     ascend: {
       label: "上昇",
     },
+    ignore_case: {
+      label: "大文字と小文字を区別しない",
+    },
+    seed: {
+      label: "シャッフルのシード",
+    },
+    errors: {
+      invalid_seed: "シードは0以上の整数でなければなりません",
+    },
   },
   list: {
     pagination: {
@@ -36,13 +58,30 @@ export default {
     errors: {
       root_not_set: "ルートパスを選んでください",
       allowed_mimes_not_set: "MIMEタイプを一つ以上選んでください",
+      watch_failed: "スキャンの進捗を取得できませんでした",
     },
     root_path: {
       label: "ルートパス",
     },
+    exclude: {
+      label: "除外するパターン",
+    },
+    skip_hidden: {
+      label: "隠しファイルを除く",
+    },
+    follow_symlinks: {
+      label: "シンボリックリンクをたどる",
+    },
     allowed_mimes: {
       label: "許可されたMIME",
     },
+    directories: {
+      title: "フォルダ",
+      files: "ファイル",
+      total_files: "以下のファイル",
+      total_size: "以下のサイズ",
+      group_checked: "チェックしたフォルダをグループする",
+    },
     ungrouped: {
       title: "グループされてない",
     },
@@ -51,6 +90,8 @@ export default {
       entry_count: "エンティティーの数",
     },
     scan: "探す",
+    progress: "{{dirs}} フォルダ、{{files}} ファイル",
+    cancel: "キャンセル",
     back: "戻る",
     submit: "送信する",
   },
@@ -1,7 +1,34 @@
+export interface MediaInfo {
+  duration: number | null;
+  width: number | null;
+  height: number | null;
+  frame_rate: number | null;
+  channels: number | null;
+  sample_rate: number | null;
+  video_codec: string | null;
+  audio_codec: string | null;
+  creation_time: string | null;
+}
+
+export interface ImageInfo {
+  width: number;
+  height: number;
+  color_type: string;
+}
+
+export type CreatedAtSource = "birth" | "modified" | "changed";
+
 export interface Meta {
   path: string;
-  created_at: string;
+  created_at: string | null;
+  created_at_source: CreatedAtSource | null;
   updated_at: string;
+  orientation: number | null;
+  inner_path: string | null;
+  media: MediaInfo | null;
+  size: number;
+  image: ImageInfo | null;
+  taken_at: string | null;
 }
 
 export enum MetaCmpBy {
@@ -9,4 +36,40 @@ export enum MetaCmpBy {
   Path = "path",
   CreatedAt = "created_at",
   UpdatedAt = "updated_at",
+  Duration = "duration",
+  Size = "size",
+  Width = "width",
+  Height = "height",
+  AspectRatio = "aspect_ratio",
+  NaturalPath = "natural_path",
+  FileName = "file_name",
+  TakenAt = "taken_at",
+  Shuffle = "shuffle",
+}
+
+export type SortBy =
+  | Exclude<MetaCmpBy, MetaCmpBy.Shuffle>
+  | { [MetaCmpBy.Shuffle]: { seed: number } };
+
+// Shuffles take a seed, which the server uses to keep them reproducible.
+export function sortBy(by: MetaCmpBy, seed: number): SortBy {
+  return by === MetaCmpBy.Shuffle ? { [MetaCmpBy.Shuffle]: { seed } } : by;
+}
+
+export function sortByKind(by: SortBy): MetaCmpBy {
+  return typeof by === "string" ? by : MetaCmpBy.Shuffle;
+}
+
+export function sortBySeed(by: SortBy): number | null {
+  return typeof by === "string" ? null : by[MetaCmpBy.Shuffle].seed;
+}
+
+export function randomSeed(): number {
+  return Math.floor(Math.random() * 2 ** 32);
+}
+
+export interface SortKey {
+  by: SortBy;
+  ascend: boolean;
+  ignore_case?: boolean;
 }
@@ -7,6 +7,7 @@ mod app_config;
 mod entries;
 mod groups;
 mod playlists;
+mod scan_jobs;
 mod scanner;
 mod session;
 
@@ -19,6 +20,14 @@ pub fn router() -> Router {
         .route("/session/write", post(session::save))
         .route("/session/read", post(session::load))
         .route("/scanner", get(scanner::index))
+        .route("/scan_jobs", post(scan_jobs::create))
+        .route(
+            "/scan_jobs/:id",
+            get(scan_jobs::show).delete(scan_jobs::destroy),
+        )
+        .route("/scan_jobs/:id/events", get(scan_jobs::events))
+        .route("/scan_jobs/:id/paths", get(scan_jobs::paths))
+        .route("/scan_jobs/:id/tree", get(scan_jobs::tree))
         .route("/playlists", get(playlists::index).post(playlists::create))
         .route(
             "/playlists/:id",
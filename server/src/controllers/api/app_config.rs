@@ -11,6 +11,9 @@ pub async fn index() -> Response {
 }
 
 pub async fn update(Json(body): Json<AppConfig>) -> Response {
+    if let Err(err) = body.validate() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response();
+    }
     body.save()
         .map(|_| StatusCode::NO_CONTENT.into_response())
         .unwrap_or_else(|err| {
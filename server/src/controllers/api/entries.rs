@@ -3,24 +3,36 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     models::entry::Entry,
-    utils::meta::{Meta, MetaCmpBy},
+    utils::meta::{Meta, SortKey},
 };
 
 #[derive(Deserialize, Serialize)]
 pub struct IndexArgs {
     group_id: Option<String>,
+    /// Only lists entries taken at or after this time, see [`Meta::taken_or_updated_at`].
+    taken_from: Option<DateTime<Utc>>,
+    /// Only lists entries taken before this time.
+    taken_until: Option<DateTime<Utc>>,
 }
 pub async fn index(Query(query): Query<IndexArgs>) -> Response {
-    if let Some(group_id) = query.group_id {
-        Json(Entry::find_by_group_id(&group_id)).into_response()
+    let entries = if let Some(group_id) = query.group_id {
+        Entry::find_by_group_id(&group_id)
     } else {
-        Json(Entry::all()).into_response()
-    }
+        Entry::all()
+    };
+    let taken = query.taken_from.unwrap_or(DateTime::<Utc>::MIN_UTC)
+        ..query.taken_until.unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let entries = entries
+        .into_iter()
+        .filter(|entry| taken.contains(&entry.meta.taken_or_updated_at()))
+        .collect::<Vec<_>>();
+    Json(entries).into_response()
 }
 
 #[derive(Deserialize, Serialize)]
@@ -51,11 +63,12 @@ pub async fn create(Json(body): Json<CreateArgs>) -> Response {
 
 #[derive(Deserialize, Serialize)]
 pub struct SortArgs {
-    by: MetaCmpBy,
-    ascend: bool,
+    keys: Vec<SortKey>,
+    #[serde(default)]
+    group_id: Option<String>,
 }
 pub async fn sort(Json(body): Json<SortArgs>) -> Response {
-    crate::services::entry::sort(body.by, body.ascend);
+    crate::services::entry::sort(&body.keys, body.group_id.as_deref());
     StatusCode::NO_CONTENT.into_response()
 }
 
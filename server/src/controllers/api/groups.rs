@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     models::group::Group,
-    utils::meta::{Meta, MetaCmpBy},
+    utils::meta::{Meta, SortKey},
 };
 
 #[derive(Deserialize, Serialize)]
@@ -35,9 +35,24 @@ pub async fn create(Json(body): Json<CreateArgs>) -> Response {
     }
     Meta::from_path(path)
         .map(|meta| {
-            Group::new(meta, body.playlist_id)
+            let group = Group::new(meta, body.playlist_id);
+            let entries = match group.archive_entries() {
+                Ok(entries) => entries,
+                Err(err) => {
+                    error!("Cannot read group archive: {}", err);
+                    return StatusCode::UNPROCESSABLE_ENTITY.into_response();
+                }
+            };
+            group
                 .save()
-                .map(|group| (StatusCode::CREATED, Json(group)).into_response())
+                .map(|group| {
+                    for entry in entries {
+                        if entry.save().is_err() {
+                            error!("Cannot save entry");
+                        }
+                    }
+                    (StatusCode::CREATED, Json(group)).into_response()
+                })
                 .unwrap_or_else(|_| {
                     error!("Cannot save group");
                     StatusCode::INTERNAL_SERVER_ERROR.into_response()
@@ -51,11 +66,12 @@ pub async fn create(Json(body): Json<CreateArgs>) -> Response {
 
 #[derive(Deserialize, Serialize)]
 pub struct SortArgs {
-    by: MetaCmpBy,
-    ascend: bool,
+    keys: Vec<SortKey>,
+    #[serde(default)]
+    playlist_id: Option<String>,
 }
 pub async fn sort(Json(body): Json<SortArgs>) -> Response {
-    crate::services::group::sort(body.by, body.ascend);
+    crate::services::group::sort(&body.keys, body.playlist_id.as_deref());
     StatusCode::NO_CONTENT.into_response()
 }
 
@@ -0,0 +1,177 @@
+use std::{convert::Infallible, path::PathBuf, time::Duration};
+
+use axum::{
+    extract::{Path, Query},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use futures_util::stream;
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::scan_job::{ScanJob, ScanJobState},
+    utils::fs::ScanOptions,
+};
+
+/// Least time between two progress events, so that fast scans do not flood clients.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+const DEFAULT_PAGE_SIZE: usize = 1000;
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateArgs {
+    root_path: String,
+    allowed_mimes: Vec<String>,
+    #[serde(flatten)]
+    options: ScanOptions,
+}
+pub async fn create(Json(body): Json<CreateArgs>) -> Response {
+    let root_path = PathBuf::from(body.root_path);
+    if !root_path.is_dir() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let job = ScanJob::start(root_path, body.allowed_mimes, body.options);
+    (StatusCode::CREATED, Json(job.status())).into_response()
+}
+
+pub async fn show(Path(id): Path<String>) -> Response {
+    ScanJob::find(&id)
+        .map(|job| Json(job.status()).into_response())
+        .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// Streams the status of a job as server-sent events, ending with the one telling how it ended.
+pub async fn events(Path(id): Path<String>) -> Result<Response, Response> {
+    let job = ScanJob::find(&id).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let events = stream::unfold(Some((job.subscribe(), true)), |state| async move {
+        let (mut receiver, first) = state?;
+        if !first {
+            receiver.changed().await.ok()?;
+            tokio::time::sleep(PROGRESS_INTERVAL).await;
+        }
+        let status = receiver.borrow_and_update().clone();
+        let next = (status.state == ScanJobState::Running).then_some((receiver, false));
+        let event = Event::default()
+            .json_data(&status)
+            .unwrap_or_else(|_| Event::default());
+        Some((Ok::<_, Infallible>(event), next))
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PathsArgs {
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+#[derive(Deserialize, Serialize)]
+pub struct PathsPage {
+    paths: Vec<String>,
+    /// How many paths matched so far.
+    total: usize,
+}
+pub async fn paths(
+    Path(id): Path<String>,
+    Query(query): Query<PathsArgs>,
+) -> Result<Response, Response> {
+    let job = ScanJob::find(&id).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let (paths, total) = job.paths(query.offset, query.limit.unwrap_or(DEFAULT_PAGE_SIZE));
+    Ok(Json(PathsPage { paths, total }).into_response())
+}
+
+/// The paths matched so far, nested under their directories with their counts and sizes.
+pub async fn tree(Path(id): Path<String>) -> Result<Response, Response> {
+    let job = ScanJob::find(&id).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    tokio::task::spawn_blocking(move || Json(job.tree()).into_response())
+        .await
+        .map_err(|err| {
+            error!("Cannot build scan tree: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        })
+}
+
+pub async fn destroy(Path(id): Path<String>) -> Response {
+    ScanJob::find(&id)
+        .map(|job| job.destroy())
+        .map(|_| StatusCode::NO_CONTENT.into_response())
+        .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use http::{header::CONTENT_TYPE, Request};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        controllers::api::router,
+        models::scan_job::ScanJobStatus,
+        utils::{fs::ScanTree, test::fixtures_dir},
+    };
+
+    async fn request(method: &str, uri: String, body: Body) -> Response {
+        router()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn scan_job() {
+        let args = CreateArgs {
+            root_path: fixtures_dir().join("b").to_string_lossy().to_string(),
+            allowed_mimes: vec!["image/".to_string()],
+            options: ScanOptions::default(),
+        };
+        let body = Body::from(serde_json::to_vec(&args).unwrap());
+        let response = request("POST", "/scan_jobs".to_string(), body).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let status: ScanJobStatus = serde_json::from_slice(&bytes).unwrap();
+
+        let uri = format!("/scan_jobs/{}/events", status.id);
+        let response = request("GET", uri, Body::empty()).await;
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let events = String::from_utf8(bytes.to_vec()).unwrap();
+        let last = events.trim_end().lines().last().unwrap();
+        let last: ScanJobStatus = serde_json::from_str(last.trim_start_matches("data: ")).unwrap();
+        assert_eq!(last.state, ScanJobState::Completed);
+        assert_eq!(last.progress.dirs_visited, 4);
+        assert_eq!(last.progress.files_matched, 5);
+        assert_eq!(last.progress.current_path, None);
+
+        let uri = format!("/scan_jobs/{}/paths?offset=1&limit=2", status.id);
+        let response = request("GET", uri, Body::empty()).await;
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let page: PathsPage = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!((page.paths.len(), page.total), (2, 5));
+
+        let uri = format!("/scan_jobs/{}/tree", status.id);
+        let response = request("GET", uri, Body::empty()).await;
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let tree: ScanTree = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!((tree.files.len(), tree.total_files), (1, 5));
+
+        let uri = format!("/scan_jobs/{}", status.id);
+        let response = request("DELETE", uri.clone(), Body::empty()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = request("GET", uri, Body::empty()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
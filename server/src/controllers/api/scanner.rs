@@ -3,23 +3,85 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
+use http::StatusCode;
 use serde::{Deserialize, Serialize};
 
-use crate::utils::fs::scan_medias;
+use crate::utils::fs::{scan_medias, ScanOptions, ScanTree};
+
+/// How the matched paths are listed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum ScanMode {
+    /// A flat list of paths.
+    #[default]
+    #[serde(rename = "list")]
+    List,
+    /// A [`ScanTree`] nesting paths under their directories.
+    #[serde(rename = "tree")]
+    Tree,
+}
 
 #[derive(Deserialize, Serialize)]
 pub struct IndexArgs {
     root_path: String,
     allowed_mimes: String,
+    #[serde(default)]
+    mode: ScanMode,
+    /// Comma separated globs, see [`ScanOptions::include`].
+    include: Option<String>,
+    /// Comma separated globs, see [`ScanOptions::exclude`].
+    exclude: Option<String>,
+    min_depth: Option<usize>,
+    max_depth: Option<usize>,
+    #[serde(default)]
+    skip_hidden: bool,
+    #[serde(default)]
+    follow_symlinks: bool,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_since: Option<DateTime<Utc>>,
+}
+
+impl IndexArgs {
+    fn options(&self) -> ScanOptions {
+        let globs = |globs: &Option<String>| {
+            globs
+                .iter()
+                .flat_map(|globs| globs.split(','))
+                .filter(|glob| !glob.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        };
+        ScanOptions {
+            include: globs(&self.include),
+            exclude: globs(&self.exclude),
+            min_depth: self.min_depth,
+            max_depth: self.max_depth,
+            skip_hidden: self.skip_hidden,
+            follow_symlinks: self.follow_symlinks,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            modified_since: self.modified_since,
+        }
+    }
 }
 pub async fn index(Query(query): Query<IndexArgs>) -> Response {
-    Json(scan_medias(
-        query.root_path,
-        query
-            .allowed_mimes
-            .split(',')
-            .map(ToString::to_string)
-            .collect(),
-    ))
-    .into_response()
+    let options = query.options();
+    let allowed_mimes = query
+        .allowed_mimes
+        .split(',')
+        .map(ToString::to_string)
+        .collect();
+    tokio::task::spawn_blocking(move || {
+        let paths = scan_medias(query.root_path.clone(), allowed_mimes, &options);
+        match query.mode {
+            ScanMode::List => Json(paths).into_response(),
+            ScanMode::Tree => Json(ScanTree::new(&query.root_path, &paths)).into_response(),
+        }
+    })
+    .await
+    .unwrap_or_else(|err| {
+        error!("Cannot scan medias: {}", err);
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    })
 }
@@ -1,115 +1,614 @@
-use std::{io::SeekFrom, ops::Deref};
+use std::ops::RangeInclusive;
 
 use axum::{
     body::Body,
-    extract::{FromRequestParts, Path},
+    extract::{Path, Query},
     response::{AppendHeaders, IntoResponse, Response},
     routing::get,
     Router,
 };
 use http::{
-    header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RANGE},
-    request::Parts,
-    StatusCode,
-};
-use http_range_header::ParsedRanges;
-use tokio::{
-    fs::File,
-    io::{AsyncReadExt, AsyncSeekExt},
+    header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE},
+    HeaderMap, Method, StatusCode,
 };
+use serde::{Deserialize, Serialize};
+use tokio::fs::File;
 use tokio_util::io::ReaderStream;
 
-use crate::models::entry::Entry;
+use crate::{
+    models::{app_config::AppConfig, entry::Entry},
+    utils::{
+        cache,
+        imaging::{self, Fit, ImageFormat, Transform},
+    },
+};
+
+use conditional::Validators;
+use range::{ByteRanges, RangesError, RangesHeader};
+use source::Source;
+
+mod artwork;
+mod conditional;
+mod download;
+mod range;
+mod read_ahead;
+mod source;
+mod subtitles;
+mod thumbnail;
+
+fn internal_error(err: impl ToString) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+}
 
-struct RangesHeader(ParsedRanges);
+/// The EXIF orientation to undo when serving an image entry, if it is not upright.
+fn orientation(entry: &Entry) -> Option<u32> {
+    entry
+        .meta
+        .orientation
+        .filter(|orientation| (2..=8).contains(orientation) && AppConfig::all().auto_orient)
+}
 
-impl Deref for RangesHeader {
-    type Target = ParsedRanges;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+/// The end of the part of `range` served at once, at most `chunk_size` bytes long if set.
+fn chunk_end(range: &RangeInclusive<u64>, chunk_size: Option<u64>) -> u64 {
+    match chunk_size.filter(|size| *size > 0) {
+        Some(size) => (*range.end()).min(range.start().saturating_add(size - 1)),
+        None => *range.end(),
     }
 }
 
-#[async_trait]
-impl<S> FromRequestParts<S> for RangesHeader
-where
-    S: Send + Sync,
-{
-    type Rejection = (StatusCode, &'static str);
-
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let range_header_str = parts
-            .headers
-            .get(RANGE)
-            .ok_or((StatusCode::BAD_REQUEST, "`Range` header is missing"))?
-            .to_str()
-            .map_err(|_| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    "`Range` header contains invalid characters",
-                )
-            })?;
+/// Serves a whole file derived from an entry, e.g. from the on-disk cache.
+async fn cached_response(
+    path: impl AsRef<std::path::Path>,
+    mime: &str,
+    method: &Method,
+    headers: &HeaderMap,
+) -> Result<Response, Response> {
+    let file = File::open(path).await.map_err(internal_error)?;
+    let metadata = file.metadata().await.map_err(internal_error)?;
+    let validators = Validators::new(&metadata).map_err(internal_error)?;
+    let mut response = if validators.not_modified(headers) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        (
+            AppendHeaders([
+                (CONTENT_TYPE, mime.to_string()),
+                (CONTENT_LENGTH, metadata.len().to_string()),
+            ]),
+            if method == Method::HEAD {
+                Body::empty()
+            } else {
+                Body::from_stream(ReaderStream::new(file))
+            },
+        )
+            .into_response()
+    };
+    validators.apply(response.headers_mut());
+    Ok(response)
+}
 
-        let ranges = http_range_header::parse_range_header(range_header_str).unwrap();
+#[derive(Deserialize, Serialize)]
+pub struct EntryArgs {
+    w: Option<u32>,
+    h: Option<u32>,
+    fit: Option<Fit>,
+    format: Option<ImageFormat>,
+    quality: Option<u8>,
+}
 
-        Ok(Self(ranges))
+impl EntryArgs {
+    /// The transform requested for an image entry, if any. Images browsers cannot render are
+    /// always converted, to PNG unless another format is requested, and images that are not
+    /// upright are always oriented. Sizes are clamped to [`imaging::MAX_SIZE`].
+    fn transform(
+        &self,
+        mime: &str,
+        orientation: Option<u32>,
+    ) -> Result<Option<Transform>, &'static str> {
+        if self
+            .quality
+            .is_some_and(|quality| !(1..=100).contains(&quality))
+        {
+            return Err("Quality must be from 1 to 100");
+        }
+        if self.quality.is_some() && self.format == Some(ImageFormat::WebP) {
+            return Err("WebP is encoded lossless, without quality");
+        }
+        let requested = self.w.is_some()
+            || self.h.is_some()
+            || self.fit.is_some()
+            || self.format.is_some()
+            || self.quality.is_some();
+        if !imaging::is_decodable(mime)
+            || !(requested || orientation.is_some() || imaging::needs_transcoding(mime))
+        {
+            return Ok(None);
+        }
+        let clamp = |size: Option<u32>| size.map(|size| size.clamp(1, imaging::MAX_SIZE));
+        Ok(Some(Transform {
+            width: clamp(self.w),
+            height: clamp(self.h),
+            fit: self.fit.unwrap_or_default(),
+            format: self
+                .format
+                .or_else(|| ImageFormat::from_mime(mime))
+                .unwrap_or(ImageFormat::Png),
+            quality: self.quality.unwrap_or(imaging::DEFAULT_QUALITY),
+            orientation,
+        }))
     }
 }
 
-async fn entry(ranges: Option<RangesHeader>, Path(id): Path<String>) -> Result<Response, Response> {
-    let entry = Entry::find(&id);
-    match entry {
-        None => Ok((StatusCode::NOT_FOUND, ()).into_response()),
-        Some(entry) => {
-            let mut file = File::open(entry.meta.path.as_str()).await.map_err(|err| {
-                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
-            })?;
-            let meta = file.metadata().await.map_err(|err| {
-                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
-            })?;
-            if entry.mime.starts_with("image/") {
-                Ok((
-                    AppendHeaders([
-                        (CONTENT_TYPE, entry.mime),
-                        (CONTENT_LENGTH, meta.len().to_string()),
-                    ]),
-                    Body::from_stream(ReaderStream::new(file)),
-                )
-                    .into_response())
-            } else if entry.mime.starts_with("video/") || entry.mime.starts_with("audio/") {
-                let range = ranges
-                    .ok_or((StatusCode::BAD_REQUEST, "Range is needed").into_response())?
-                    .validate(meta.len())
-                    .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()).into_response())?
-                    .first()
-                    .ok_or((StatusCode::BAD_REQUEST, "Range is needed").into_response())?
-                    .clone();
-                let start = *range.start();
-                let end = (*range.end()).min(meta.len() - 1).min(start + 999_999);
-                let content_length = end - start + 1;
-                let content_range = format!("bytes {start}-{end}/{}", meta.len());
-                file.seek(SeekFrom::Start(start)).await.map_err(|err| {
-                    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
-                })?;
-                let file = file.take(content_length);
-                Ok((
-                    StatusCode::PARTIAL_CONTENT,
-                    AppendHeaders([
-                        (CONTENT_TYPE, entry.mime),
-                        (CONTENT_LENGTH, content_length.to_string()),
-                        (CONTENT_RANGE, content_range),
-                        (ACCEPT_RANGES, "bytes".to_string()),
-                    ]),
-                    Body::from_stream(ReaderStream::new(file)),
-                )
-                    .into_response())
+async fn entry(
+    method: Method,
+    headers: HeaderMap,
+    ranges: Option<RangesHeader>,
+    Path(id): Path<String>,
+    Query(query): Query<EntryArgs>,
+) -> Result<Response, Response> {
+    let entry = Entry::find(&id).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let transform = query
+        .transform(&entry.mime, orientation(&entry))
+        .map_err(|err| (StatusCode::BAD_REQUEST, err).into_response())?;
+    if let Some(transform) = transform {
+        let key = cache::key((entry.meta.location(), entry.meta.updated_at, transform));
+        let meta = entry.meta.clone();
+        let image = cache::get_or_create("images", key, move || transform.apply(&meta.read()?))
+            .await
+            .map_err(internal_error)?;
+        return cached_response(image, transform.format.mime(), &method, &headers).await;
+    }
+    let mime = if entry.mime.is_empty() {
+        mime::APPLICATION_OCTET_STREAM.to_string()
+    } else {
+        entry.mime
+    };
+    let source = Source::new(&entry.meta);
+    let (metadata, len) = source.stat().await.map_err(internal_error)?;
+    let validators = Validators::new(&metadata).map_err(internal_error)?;
+    if validators.not_modified(&headers) {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        validators.apply(response.headers_mut());
+        return Ok(response);
+    }
+    let ranges = ranges.filter(|_| validators.if_range(&headers));
+    let with_body = method != Method::HEAD;
+
+    let mut response = match ranges.map(|ranges| ranges.coalesce(len)) {
+        None | Some(Err(RangesError::Invalid | RangesError::TooMany)) => Ok((
+            AppendHeaders([
+                (CONTENT_TYPE, mime),
+                (CONTENT_LENGTH, len.to_string()),
+                (ACCEPT_RANGES, "bytes".to_string()),
+            ]),
+            if with_body {
+                Body::from_stream(ReaderStream::new(
+                    source.reader(0, len).await.map_err(internal_error)?,
+                ))
             } else {
-                Err((StatusCode::INTERNAL_SERVER_ERROR, "Unknown mime type").into_response())
-            }
+                Body::empty()
+            },
+        )
+            .into_response()),
+        Some(Err(RangesError::Unsatisfiable)) => Ok((
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            AppendHeaders([
+                (CONTENT_RANGE, format!("bytes */{len}")),
+                (ACCEPT_RANGES, "bytes".to_string()),
+            ]),
+        )
+            .into_response()),
+        Some(Ok(ranges)) if ranges.len() > 1 => {
+            let byte_ranges = ByteRanges::new(&mime, len, &ranges);
+            let content_type = format!("multipart/byteranges; boundary={}", byte_ranges.boundary);
+            let content_length = byte_ranges.content_length();
+            Ok((
+                StatusCode::PARTIAL_CONTENT,
+                AppendHeaders([
+                    (CONTENT_TYPE, content_type),
+                    (CONTENT_LENGTH, content_length.to_string()),
+                    (ACCEPT_RANGES, "bytes".to_string()),
+                ]),
+                if with_body {
+                    byte_ranges.into_body(source)
+                } else {
+                    Body::empty()
+                },
+            )
+                .into_response())
         }
-    }
+        Some(Ok(ranges)) => {
+            let app_config = AppConfig::all();
+            let start = *ranges[0].start();
+            let end = chunk_end(&ranges[0], app_config.range_chunk_size);
+            let content_length = end - start + 1;
+            let content_range = format!("bytes {start}-{end}/{len}");
+            let body = if !with_body {
+                Body::empty()
+            } else if let Some(bytes) =
+                read_ahead::get(&entry.id, validators.etag(), &(start..=end))
+            {
+                Body::from(bytes)
+            } else {
+                match app_config.read_ahead_size {
+                    Some(size) if size >= content_length => {
+                        let size = size.min(len - start);
+                        let reader = source.reader(start, size).await.map_err(internal_error)?;
+                        Body::from(
+                            read_ahead::fill(reader, &entry.id, validators.etag(), start, size)
+                                .await
+                                .map_err(internal_error)?
+                                .slice(..content_length as usize),
+                        )
+                    }
+                    _ => Body::from_stream(ReaderStream::new(
+                        source
+                            .reader(start, content_length)
+                            .await
+                            .map_err(internal_error)?,
+                    )),
+                }
+            };
+            Ok((
+                StatusCode::PARTIAL_CONTENT,
+                AppendHeaders([
+                    (CONTENT_TYPE, mime),
+                    (CONTENT_LENGTH, content_length.to_string()),
+                    (CONTENT_RANGE, content_range),
+                    (ACCEPT_RANGES, "bytes".to_string()),
+                ]),
+                body,
+            )
+                .into_response())
+        }
+    }?;
+    validators.apply(response.headers_mut());
+    Ok(response)
 }
 
 pub fn router() -> Router {
-    Router::new().route("/entries/:id", get(entry))
+    Router::new()
+        .route("/entries/:id", get(entry))
+        .route("/entries/:id/thumbnail", get(thumbnail::show))
+        .route("/entries/:id/artwork", get(artwork::show))
+        .route("/entries/:id/subtitles/:lang", get(subtitles::show))
+        .route("/groups/:id/archive", get(download::group))
+        .route("/playlists/:id/archive", get(download::playlist))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use http::{
+        header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, RANGE},
+        HeaderName, Request,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        models::group::Group,
+        utils::{meta::Meta, test::fixtures_dir},
+    };
+
+    const VIDEO: &str = "a/a/a/1.mp4";
+    const VIDEO_LEN: usize = 13539;
+
+    const IMAGE: &str = "b/1.png";
+    const IMAGE_LEN: usize = 226;
+
+    async fn get_entry(path: &str, range: Option<&str>) -> Response {
+        request_entry(Method::GET, path, range.map(|range| (RANGE, range))).await
+    }
+
+    fn saved_entry(path: &str) -> Entry {
+        Entry::new(
+            Meta::from_path(fixtures_dir().join(path)).unwrap(),
+            "stream".to_string(),
+        )
+        .save()
+        .unwrap()
+    }
+
+    async fn request_entry<'a>(
+        method: Method,
+        path: &str,
+        headers: impl IntoIterator<Item = (HeaderName, &'a str)>,
+    ) -> Response {
+        let entry = saved_entry(path);
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("/entries/{}", entry.id));
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        router()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_len(response: Response) -> usize {
+        to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .len()
+    }
+
+    #[tokio::test]
+    async fn full_content() {
+        for (path, len) in [(VIDEO, VIDEO_LEN), (IMAGE, IMAGE_LEN)] {
+            let response = get_entry(path, None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[ACCEPT_RANGES], "bytes");
+            assert_eq!(body_len(response).await, len);
+        }
+    }
+
+    #[tokio::test]
+    async fn head() {
+        let response = request_entry(Method::HEAD, IMAGE, []).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "image/png");
+        assert_eq!(response.headers()[CONTENT_LENGTH], IMAGE_LEN.to_string());
+        assert_eq!(body_len(response).await, 0);
+    }
+
+    #[tokio::test]
+    async fn image_range() {
+        let response = get_entry(IMAGE, Some("bytes=0-7")).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body_len(response).await, 8);
+    }
+
+    #[tokio::test]
+    async fn transformed_image() {
+        let entry = saved_entry(IMAGE);
+        let response = router()
+            .oneshot(
+                Request::get(format!("/entries/{}?w=8&format=jpeg", entry.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "image/jpeg");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(image::load_from_memory(&bytes).unwrap().width(), 8);
+    }
+
+    #[tokio::test]
+    async fn invalid_transform() {
+        let entry = saved_entry(IMAGE);
+        for query in ["quality=0", "quality=101", "format=webp&quality=80"] {
+            let response = router()
+                .oneshot(
+                    Request::get(format!("/entries/{}?{}", entry.id, query))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn transcoded_image() {
+        for path in ["d/1.bmp", "d/1.qoi"] {
+            let response = get_entry(path, None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[CONTENT_TYPE], "image/png");
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(image::load_from_memory(&bytes).unwrap().width(), 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn oriented_image() {
+        let response = get_entry("d/2.jpg", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let image = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 4));
+    }
+
+    #[tokio::test]
+    async fn thumbnail() {
+        for (path, status) in [
+            (IMAGE, StatusCode::OK),
+            ("c/1.svg", StatusCode::UNSUPPORTED_MEDIA_TYPE),
+            (VIDEO, StatusCode::NOT_FOUND),
+        ] {
+            let entry = saved_entry(path);
+            let response = router()
+                .oneshot(
+                    Request::get(format!("/entries/{}/thumbnail", entry.id))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_range_serves_full_content() {
+        for range in ["bytes=abc", "items=0-1", "bytes=10-5"] {
+            let response = get_entry(VIDEO, Some(range)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(body_len(response).await, VIDEO_LEN);
+        }
+    }
+
+    #[tokio::test]
+    async fn unsatisfiable_range() {
+        let response = get_entry(VIDEO, Some("bytes=20000-")).await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            response.headers()[CONTENT_RANGE],
+            format!("bytes */{VIDEO_LEN}")
+        );
+    }
+
+    #[tokio::test]
+    async fn single_range() {
+        let response = get_entry(VIDEO, Some("bytes=20000-,-10")).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers()[CONTENT_RANGE],
+            format!("bytes {}-{}/{VIDEO_LEN}", VIDEO_LEN - 10, VIDEO_LEN - 1)
+        );
+        assert_eq!(body_len(response).await, 10);
+    }
+
+    #[tokio::test]
+    async fn multiple_ranges() {
+        let response = get_entry(VIDEO, Some("bytes=0-3,2-5,10-12")).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert!(response.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("multipart/byteranges; boundary="));
+        let content_length = response.headers()[CONTENT_LENGTH].clone();
+        assert_eq!(body_len(response).await.to_string(), content_length);
+    }
+
+    #[tokio::test]
+    async fn too_many_ranges() {
+        let ranges = (0..100)
+            .map(|index| format!("{0}-{0}", index * 2))
+            .collect::<Vec<_>>()
+            .join(",");
+        let response = get_entry(VIDEO, Some(&format!("bytes={ranges}"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_len(response).await, VIDEO_LEN);
+    }
+
+    #[test]
+    fn chunk_end() {
+        assert_eq!(super::chunk_end(&(10..=99), Some(50)), 59);
+        assert_eq!(super::chunk_end(&(10..=99), Some(0)), 99);
+        assert_eq!(super::chunk_end(&(10..=99), None), 99);
+        assert_eq!(super::chunk_end(&(10..=99), Some(u64::MAX)), 99);
+        assert_eq!(super::chunk_end(&(10..=u64::MAX), Some(u64::MAX)), u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn conditional_get() {
+        let response = get_entry(IMAGE, None).await;
+        let etag = response.headers()[ETAG].to_str().unwrap().to_string();
+        let last_modified = response.headers()[LAST_MODIFIED]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = request_entry(Method::GET, IMAGE, [(IF_NONE_MATCH, etag.as_str())]).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(body_len(response).await, 0);
+
+        let response = request_entry(Method::GET, IMAGE, [(IF_NONE_MATCH, "\"stale\"")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = request_entry(
+            Method::GET,
+            IMAGE,
+            [(IF_MODIFIED_SINCE, last_modified.as_str())],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn if_range() {
+        let etag = get_entry(VIDEO, None).await.headers()[ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = request_entry(
+            Method::GET,
+            VIDEO,
+            [(RANGE, "bytes=0-9"), (IF_RANGE, etag.as_str())],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+
+        let response = request_entry(
+            Method::GET,
+            VIDEO,
+            [(RANGE, "bytes=0-9"), (IF_RANGE, "\"stale\"")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_len(response).await, VIDEO_LEN);
+    }
+
+    #[tokio::test]
+    async fn archive_member() {
+        let group = Group::new(
+            Meta::from_path(fixtures_dir().join("e/1.cbz")).unwrap(),
+            "stream".to_string(),
+        );
+        let entries = group.archive_entries().unwrap();
+        assert_eq!(entries.len(), 3);
+        let entry = entries
+            .into_iter()
+            .find(|entry| entry.meta.inner_path.as_deref() == Some("pages/2.png"))
+            .unwrap()
+            .save()
+            .unwrap();
+        let expected = entry.meta.read().unwrap();
+        let request = |range: &'static str| {
+            Request::builder()
+                .uri(format!("/entries/{}", entry.id))
+                .header(RANGE, range)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = router().oneshot(request("bytes=10-19")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers()[CONTENT_RANGE],
+            format!("bytes 10-19/{}", expected.len())
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, expected[10..20]);
+
+        let response = router().oneshot(request("bytes=0-1,-2")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body.windows(2).any(|bytes| *bytes == expected[..2]));
+        assert!(body
+            .windows(2)
+            .any(|bytes| *bytes == expected[expected.len() - 2..]));
+    }
+
+    #[tokio::test]
+    async fn artwork() {
+        for (path, status, mime) in [
+            ("f/a/1.mp3", StatusCode::OK, "image/png"),
+            ("f/b/1.mp3", StatusCode::OK, "image/jpeg"),
+            (VIDEO, StatusCode::NOT_FOUND, ""),
+            (IMAGE, StatusCode::NOT_FOUND, ""),
+        ] {
+            let entry = saved_entry(path);
+            let response = router()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/entries/{}/artwork", entry.id))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "{path}");
+            if status == StatusCode::OK {
+                assert_eq!(response.headers()[CONTENT_TYPE], mime, "{path}");
+                assert!(response.headers().contains_key(ETAG));
+            }
+        }
+    }
 }
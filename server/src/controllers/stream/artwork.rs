@@ -0,0 +1,65 @@
+use axum::{
+    body::Body,
+    extract::Path,
+    response::{AppendHeaders, IntoResponse, Response},
+};
+use http::{
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
+    HeaderMap, Method, StatusCode,
+};
+use tokio::task::spawn_blocking;
+
+use super::{cached_response, conditional::Validators, internal_error};
+use crate::{
+    models::entry::Entry,
+    utils::{artwork, fs::guess_mime},
+};
+
+pub async fn show(
+    method: Method,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Response, Response> {
+    let entry = Entry::find(&id)
+        .filter(|entry| entry.meta.inner_path.is_none())
+        .filter(|entry| entry.mime.starts_with("audio/") || entry.mime.starts_with("video/"))
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let path = entry.meta.path.clone();
+    let embedded = spawn_blocking(move || artwork::embedded(path))
+        .await
+        .map_err(internal_error)?
+        .unwrap_or_else(|err| {
+            debug!("Cannot read embedded artwork: {}", err);
+            None
+        });
+    let Some(embedded) = embedded else {
+        let sidecar = artwork::sidecar(entry.meta.path.as_str())
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "Entry has no artwork").into_response())?;
+        let mime = guess_mime(&sidecar).unwrap_or_default();
+        return cached_response(sidecar, &mime, &method, &headers).await;
+    };
+
+    // The artwork changes along with the media file, so the file validates it.
+    let metadata = tokio::fs::metadata(entry.meta.path.as_str())
+        .await
+        .map_err(internal_error)?;
+    let validators = Validators::new(&metadata).map_err(internal_error)?;
+    let mut response = if validators.not_modified(&headers) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        (
+            AppendHeaders([
+                (CONTENT_TYPE, embedded.mime),
+                (CONTENT_LENGTH, embedded.bytes.len().to_string()),
+            ]),
+            if method == Method::HEAD {
+                Body::empty()
+            } else {
+                Body::from(embedded.bytes)
+            },
+        )
+            .into_response()
+    };
+    validators.apply(response.headers_mut());
+    Ok(response)
+}
@@ -0,0 +1,89 @@
+use std::fs::Metadata;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use http::{
+    header::{CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED},
+    HeaderMap, HeaderValue,
+};
+
+use crate::utils::system_time_to_utc;
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Validators of the file currently backing an entry, read from disk on every request so that a
+/// file changed after the entry was created is never mistaken for the cached one.
+pub struct Validators {
+    etag: String,
+    last_modified: DateTime<Utc>,
+}
+
+impl Validators {
+    pub fn new(metadata: &Metadata) -> Result<Self> {
+        let last_modified = system_time_to_utc(&metadata.modified()?)?;
+        Ok(Self {
+            etag: format!(
+                "\"{:x}-{:x}\"",
+                metadata.len(),
+                last_modified.timestamp_millis()
+            ),
+            last_modified,
+        })
+    }
+
+    pub fn etag(&self) -> &str {
+        self.etag.as_str()
+    }
+
+    fn last_modified_header(&self) -> String {
+        self.last_modified.format(HTTP_DATE_FORMAT).to_string()
+    }
+
+    fn parse_date(value: &HeaderValue) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc2822(value.to_str().ok()?)
+            .ok()
+            .map(|date| date.with_timezone(&Utc))
+    }
+
+    /// Whether the client's cached copy is still fresh according to `If-None-Match` or, when
+    /// absent, `If-Modified-Since`.
+    pub fn not_modified(&self, headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = headers.get(IF_NONE_MATCH) {
+            return if_none_match.to_str().is_ok_and(|tags| {
+                tags.split(',')
+                    .map(str::trim)
+                    .any(|tag| tag == "*" || tag.trim_start_matches("W/") == self.etag.as_str())
+            });
+        }
+        headers
+            .get(IF_MODIFIED_SINCE)
+            .and_then(Self::parse_date)
+            .is_some_and(|since| self.last_modified.timestamp() <= since.timestamp())
+    }
+
+    /// Whether a `Range` header may be honoured, i.e. `If-Range` is absent or still identifies
+    /// the current representation.
+    pub fn if_range(&self, headers: &HeaderMap) -> bool {
+        match headers.get(IF_RANGE) {
+            None => true,
+            Some(if_range) => match if_range.to_str() {
+                Ok(tag) if tag.starts_with('"') => tag == self.etag,
+                Ok(_) => Self::parse_date(if_range)
+                    .is_some_and(|date| date.timestamp() == self.last_modified.timestamp()),
+                Err(_) => false,
+            },
+        }
+    }
+
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        for (name, value) in [
+            (ETAG, self.etag.clone()),
+            (LAST_MODIFIED, self.last_modified_header()),
+            (CACHE_CONTROL, "no-cache".to_string()),
+        ] {
+            if let Ok(value) = HeaderValue::from_str(value.as_str()) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::{collections::HashSet, fs::File, io::Read};
+
+use anyhow::Result;
+use axum::{
+    body::Body,
+    extract::Path,
+    response::{AppendHeaders, IntoResponse, Response},
+};
+use http::{
+    header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    StatusCode,
+};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use tokio_util::io::ReaderStream;
+
+use super::source::blocking_reader;
+use crate::{
+    models::{entry::Entry, group::Group, playlist::Playlist},
+    utils::{archive, meta::Meta, zip_stream::ZipStream},
+};
+
+pub async fn group(Path(id): Path<String>) -> Result<Response, Response> {
+    let group = Group::find(&id).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let mut names = Names::default();
+    let members = Entry::find_by_group_id(&group.id)
+        .into_iter()
+        .map(|entry| (names.claim(entry.meta.file_name()), entry.meta))
+        .collect();
+    Ok(zip_response(group.meta.file_name(), members))
+}
+
+pub async fn playlist(Path(id): Path<String>) -> Result<Response, Response> {
+    let playlist = Playlist::find(&id).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let mut names = Names::default();
+    let mut members = Vec::new();
+    for group in Group::find_by_playlist_id(&playlist.id) {
+        let directory = names.claim(group.meta.file_name());
+        for entry in Entry::find_by_group_id(&group.id) {
+            let name = names.claim(format!("{}/{}", directory, entry.meta.file_name()));
+            members.push((name, entry.meta));
+        }
+    }
+    Ok(zip_response(playlist.name, members))
+}
+
+/// Names already used in an archive, so that entries sharing a file name do not overwrite each
+/// other when extracted.
+#[derive(Default)]
+struct Names(HashSet<String>);
+
+impl Names {
+    /// Returns `name`, or `name (2)`, `name (3)`... before the extension if it is already taken.
+    fn claim(&mut self, name: String) -> String {
+        let (stem, extension) = match name.rfind('.').filter(|index| {
+            *index > 0 && !name[*index..].contains('/') && !name[..*index].ends_with('/')
+        }) {
+            Some(index) => name.split_at(index),
+            None => (name.as_str(), ""),
+        };
+        let mut candidate = name.clone();
+        let mut index = 1;
+        while self.0.contains(&candidate) {
+            index += 1;
+            candidate = format!("{stem} ({index}){extension}");
+        }
+        self.0.insert(candidate.clone());
+        candidate
+    }
+}
+
+/// Streams a ZIP of `members` as it is produced, without buffering it in memory or on disk.
+fn zip_response(name: String, members: Vec<(String, Meta)>) -> Response {
+    let reader = blocking_reader(move |writer| {
+        let mut zip = ZipStream::new(writer);
+        for (name, meta) in members {
+            match meta.inner_path.as_ref() {
+                Some(inner_path) => {
+                    let size = archive::size(meta.path.as_str(), inner_path)?;
+                    zip.add(&name, meta.updated_at, size, |writer| {
+                        archive::copy(meta.path.as_str(), inner_path, 0, size, writer)
+                    })?;
+                }
+                None => {
+                    let file = File::open(meta.path.as_str())?;
+                    let size = file.metadata()?.len();
+                    zip.add(&name, meta.updated_at, size, |writer| {
+                        std::io::copy(&mut file.take(size), writer)?;
+                        Ok(())
+                    })?;
+                }
+            }
+        }
+        zip.finish()?;
+        Ok(())
+    });
+    (
+        AppendHeaders([
+            (CONTENT_TYPE, "application/zip".to_string()),
+            (CONTENT_DISPOSITION, content_disposition(&name)),
+        ]),
+        Body::from_stream(ReaderStream::new(reader)),
+    )
+        .into_response()
+}
+
+/// An attachment named `name.zip`, with an ASCII fallback for clients ignoring `filename*`.
+fn content_disposition(name: &str) -> String {
+    let fallback = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!(
+        "attachment; filename=\"{fallback}.zip\"; filename*=UTF-8''{}.zip",
+        utf8_percent_encode(name, NON_ALPHANUMERIC)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use axum::body::to_bytes;
+    use http::Request;
+    use tower::ServiceExt;
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::{controllers::stream::router, utils::test::fixtures_dir};
+
+    #[test]
+    fn claim() {
+        let mut names = Names::default();
+        assert_eq!(names.claim("1.mp4".to_string()), "1.mp4");
+        assert_eq!(names.claim("1.mp4".to_string()), "1 (2).mp4");
+        assert_eq!(names.claim("1.mp4".to_string()), "1 (3).mp4");
+        assert_eq!(names.claim("a.b/c".to_string()), "a.b/c");
+        assert_eq!(names.claim("a.b/c".to_string()), "a.b/c (2)");
+        assert_eq!(names.claim(".hidden".to_string()), ".hidden");
+        assert_eq!(names.claim(".hidden".to_string()), ".hidden (2)");
+    }
+
+    #[tokio::test]
+    async fn playlist_archive() {
+        let playlist = Playlist::new("Download ü".to_string()).save().unwrap();
+        for path in ["b", "e/1.cbz"] {
+            let group = Group::new(
+                Meta::from_path(fixtures_dir().join(path)).unwrap(),
+                playlist.id.clone(),
+            )
+            .save()
+            .unwrap();
+            let entries = if path == "b" {
+                vec![Entry::new(
+                    Meta::from_path(fixtures_dir().join("b/1.png")).unwrap(),
+                    group.id.clone(),
+                )]
+            } else {
+                group.archive_entries().unwrap()
+            };
+            for entry in entries {
+                entry.save().unwrap();
+            }
+        }
+
+        let response = router()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/playlists/{}/archive", playlist.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/zip");
+        assert_eq!(
+            response.headers()[CONTENT_DISPOSITION],
+            "attachment; filename=\"Download _.zip\"; filename*=UTF-8''Download%20%C3%BC.zip"
+        );
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let names = (0..archive.len())
+            .map(|index| archive.by_index(index).unwrap().name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["b/1.png", "1.cbz/1.jpg", "1.cbz/2.png", "1.cbz/10.png"]
+        );
+        let mut content = Vec::new();
+        archive
+            .by_name("1.cbz/2.png")
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        assert_eq!(
+            content,
+            archive::read(fixtures_dir().join("e/1.cbz"), "pages/2.png").unwrap()
+        );
+    }
+}
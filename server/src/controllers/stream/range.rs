@@ -0,0 +1,220 @@
+use std::{future::ready, io, ops::RangeInclusive, sync::Arc};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::FromRequestParts,
+};
+use futures_util::{stream, StreamExt};
+use http::{header::RANGE, request::Parts, StatusCode};
+use http_range_header::{EndPosition, ParsedRanges, StartPosition};
+use rand::{distributions::Alphanumeric, Rng};
+use tokio_util::io::ReaderStream;
+
+use super::source::Source;
+
+/// Most ranges served as parts of one response, since each part opens its own reader.
+const MAX_RANGES: usize = 16;
+
+pub struct RangesHeader(ParsedRanges);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RangesHeader
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let range_header_str = parts
+            .headers
+            .get(RANGE)
+            .ok_or((StatusCode::BAD_REQUEST, "`Range` header is missing"))?
+            .to_str()
+            .map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    "`Range` header contains invalid characters",
+                )
+            })?;
+
+        let ranges = http_range_header::parse_range_header(range_header_str)
+            .map_err(|_| (StatusCode::BAD_REQUEST, "`Range` header is malformed"))?;
+
+        Ok(Self(ranges))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RangesError {
+    /// A range ends before it starts, so the whole header must be ignored.
+    Invalid,
+    /// None of the ranges overlaps the representation.
+    Unsatisfiable,
+    /// More than [`MAX_RANGES`] ranges remain once merged, so the whole representation is sent
+    /// instead, as RFC 7233 section 6.1 allows.
+    TooMany,
+}
+
+impl RangesHeader {
+    /// Resolves the requested ranges against a representation of `len` bytes, sorted by start
+    /// position with overlapping and adjacent ranges merged into one. Ranges starting beyond the
+    /// end of the representation are dropped.
+    pub fn coalesce(&self, len: u64) -> Result<Vec<RangeInclusive<u64>>, RangesError> {
+        let mut ranges = Vec::with_capacity(self.0.ranges.len());
+        for range in self.0.ranges.iter() {
+            match (range.start, range.end) {
+                (StartPosition::Index(start), EndPosition::Index(end)) if end < start => {
+                    return Err(RangesError::Invalid);
+                }
+                (StartPosition::Index(start), _) if start >= len => {}
+                (StartPosition::Index(start), EndPosition::Index(end)) => {
+                    ranges.push(start..=end.min(len - 1));
+                }
+                (StartPosition::Index(start), EndPosition::LastByte) => {
+                    ranges.push(start..=len - 1);
+                }
+                (StartPosition::FromLast(_), _) if len == 0 => {}
+                (StartPosition::FromLast(suffix), _) => {
+                    ranges.push(len.saturating_sub(suffix)..=len - 1);
+                }
+            }
+        }
+        if ranges.is_empty() {
+            return Err(RangesError::Unsatisfiable);
+        }
+        ranges.sort_by_key(|range| *range.start());
+        let merged =
+            ranges
+                .into_iter()
+                .fold(Vec::<RangeInclusive<u64>>::new(), |mut merged, range| {
+                    match merged.last_mut() {
+                        Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                            *last = *last.start()..=*last.end().max(range.end());
+                        }
+                        _ => merged.push(range),
+                    }
+                    merged
+                });
+        if merged.len() > MAX_RANGES {
+            return Err(RangesError::TooMany);
+        }
+        Ok(merged)
+    }
+}
+
+/// A `multipart/byteranges` payload for several ranges of the same file.
+pub struct ByteRanges {
+    pub boundary: String,
+    parts: Vec<(String, RangeInclusive<u64>)>,
+    tail: String,
+}
+
+impl ByteRanges {
+    pub fn new(mime: &str, len: u64, ranges: &[RangeInclusive<u64>]) -> Self {
+        let boundary = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect::<String>();
+        let parts = ranges
+            .iter()
+            .map(|range| {
+                (
+                    format!(
+                        "\r\n--{boundary}\r\nContent-Type: {mime}\r\nContent-Range: bytes {}-{}/{len}\r\n\r\n",
+                        range.start(),
+                        range.end()
+                    ),
+                    range.clone(),
+                )
+            })
+            .collect();
+        let tail = format!("\r\n--{boundary}--\r\n");
+        Self {
+            boundary,
+            parts,
+            tail,
+        }
+    }
+
+    pub fn content_length(&self) -> u64 {
+        self.parts
+            .iter()
+            .map(|(head, range)| head.len() as u64 + range.end() - range.start() + 1)
+            .sum::<u64>()
+            + self.tail.len() as u64
+    }
+
+    /// Streams the parts one after another, opening the reader of each only once the previous
+    /// one is done.
+    pub fn into_body(self, source: Source) -> Body {
+        let source = Arc::new(source);
+        let parts = stream::iter(self.parts).flat_map(move |(head, range)| {
+            let source = source.clone();
+            let content = stream::once(async move {
+                source
+                    .reader(*range.start(), range.end() - range.start() + 1)
+                    .await
+            })
+            .flat_map(|reader| match reader {
+                Ok(reader) => ReaderStream::new(reader).left_stream(),
+                Err(err) => stream::once(ready(Err(io::Error::other(err)))).right_stream(),
+            });
+            stream::once(ready(Ok(Bytes::from(head)))).chain(content)
+        });
+        let tail = stream::once(ready(Ok(Bytes::from(self.tail))));
+        Body::from_stream(parts.chain(tail))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(header: &str) -> RangesHeader {
+        RangesHeader(http_range_header::parse_range_header(header).unwrap())
+    }
+
+    #[test]
+    fn coalesce() {
+        assert_eq!(ranges("bytes=0-9").coalesce(100).unwrap(), vec![0..=9]);
+        assert_eq!(
+            ranges("bytes=50-59,0-9").coalesce(100).unwrap(),
+            vec![0..=9, 50..=59]
+        );
+        assert_eq!(
+            ranges("bytes=0-9,5-19,20-29").coalesce(100).unwrap(),
+            vec![0..=29]
+        );
+        assert_eq!(
+            ranges("bytes=-10,0-9,95-").coalesce(100).unwrap(),
+            vec![0..=9, 90..=99]
+        );
+        assert_eq!(ranges("bytes=100-,0-0").coalesce(100).unwrap(), vec![0..=0]);
+        assert_eq!(
+            ranges("bytes=100-").coalesce(100),
+            Err(RangesError::Unsatisfiable)
+        );
+        assert_eq!(
+            ranges("bytes=-1").coalesce(0),
+            Err(RangesError::Unsatisfiable)
+        );
+        assert_eq!(ranges("bytes=9-0").coalesce(100), Err(RangesError::Invalid));
+        let many = (0..=MAX_RANGES)
+            .map(|index| format!("{0}-{0}", index * 2))
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(
+            ranges(&format!("bytes={many}")).coalesce(100),
+            Err(RangesError::TooMany)
+        );
+        let adjacent = (0..=MAX_RANGES)
+            .map(|index| format!("{0}-{0}", index))
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(
+            ranges(&format!("bytes={adjacent}")).coalesce(100).unwrap(),
+            vec![0..=MAX_RANGES as u64]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::{collections::VecDeque, ops::RangeInclusive};
+
+use axum::body::Bytes;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// At most this many entries keep a read-ahead buffer at the same time.
+const MAX_BUFFERS: usize = 8;
+
+struct Buffer {
+    id: String,
+    etag: String,
+    start: u64,
+    bytes: Bytes,
+}
+
+/// Returns the requested range of an entry if a previous request already read it ahead.
+pub fn get(id: &str, etag: &str, range: &RangeInclusive<u64>) -> Option<Bytes> {
+    BUFFERS.lock().iter().find_map(|buffer| {
+        let end = buffer.start + buffer.bytes.len() as u64;
+        if buffer.id == id
+            && buffer.etag == etag
+            && buffer.start <= *range.start()
+            && *range.end() < end
+        {
+            let offset = (range.start() - buffer.start) as usize;
+            let len = (range.end() - range.start() + 1) as usize;
+            Some(buffer.bytes.slice(offset..offset + len))
+        } else {
+            None
+        }
+    })
+}
+
+/// Reads the `size` bytes of `reader`, which must start at `start` in the entry, and keeps them
+/// as the read-ahead buffer of the entry.
+pub async fn fill(
+    mut reader: impl AsyncRead + Unpin,
+    id: &str,
+    etag: &str,
+    start: u64,
+    size: u64,
+) -> std::io::Result<Bytes> {
+    let mut bytes = Vec::with_capacity(size as usize);
+    reader.read_to_end(&mut bytes).await?;
+    let bytes = Bytes::from(bytes);
+    let mut buffers = BUFFERS.lock();
+    buffers.retain(|buffer| buffer.id != id);
+    if buffers.len() >= MAX_BUFFERS {
+        buffers.pop_front();
+    }
+    buffers.push_back(Buffer {
+        id: id.to_string(),
+        etag: etag.to_string(),
+        start,
+        bytes: bytes.clone(),
+    });
+    Ok(bytes)
+}
+
+static BUFFERS: Lazy<Mutex<VecDeque<Buffer>>> = Lazy::new(Mutex::default);
+
+#[cfg(test)]
+mod tests {
+    use std::io::SeekFrom;
+
+    use tokio::{
+        fs::File,
+        io::{AsyncReadExt, AsyncSeekExt},
+    };
+
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    #[tokio::test]
+    async fn fill_and_get() {
+        let path = fixtures_dir().join("c/a/2.mp4");
+        let mut file = File::open(&path).await.unwrap();
+        file.seek(SeekFrom::Start(100)).await.unwrap();
+        let bytes = fill(file.take(1000), "read_ahead", "\"etag\"", 100, 1000)
+            .await
+            .unwrap();
+        assert_eq!(bytes.len(), 1000);
+
+        let expected = std::fs::read(&path).unwrap();
+        assert_eq!(
+            get("read_ahead", "\"etag\"", &(600..=699)).unwrap(),
+            expected[600..700]
+        );
+        assert!(get("read_ahead", "\"etag\"", &(600..=1100)).is_none());
+        assert!(get("read_ahead", "\"other\"", &(600..=699)).is_none());
+    }
+}
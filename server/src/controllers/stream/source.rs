@@ -0,0 +1,83 @@
+use std::{fs::Metadata, io::SeekFrom, pin::Pin};
+
+use anyhow::Result;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, DuplexStream},
+    task::spawn_blocking,
+};
+use tokio_util::io::SyncIoBridge;
+
+use crate::utils::{archive, meta::Meta};
+
+/// Buffer between a blocking thread producing content and the response body.
+const PIPE_SIZE: usize = 64 * 1024;
+
+pub type Reader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// Where the bytes of an entry are read from.
+pub enum Source {
+    File(String),
+    Member { archive: String, name: String },
+}
+
+impl Source {
+    pub fn new(meta: &Meta) -> Self {
+        match meta.inner_path.as_ref() {
+            Some(name) => Self::Member {
+                archive: meta.path.clone(),
+                name: name.clone(),
+            },
+            None => Self::File(meta.path.clone()),
+        }
+    }
+
+    /// The metadata of the file on disk, i.e. the archive for members, and the content length.
+    pub async fn stat(&self) -> Result<(Metadata, u64)> {
+        match self {
+            Self::File(path) => {
+                let metadata = tokio::fs::metadata(path).await?;
+                let len = metadata.len();
+                Ok((metadata, len))
+            }
+            Self::Member { archive, name } => {
+                let metadata = tokio::fs::metadata(archive).await?;
+                let (archive, name) = (archive.clone(), name.clone());
+                let len = spawn_blocking(move || archive::size(archive, name.as_str())).await??;
+                Ok((metadata, len))
+            }
+        }
+    }
+
+    /// Reads `len` bytes of the content starting at `start`.
+    pub async fn reader(&self, start: u64, len: u64) -> Result<Reader> {
+        match self {
+            Self::File(path) => {
+                let mut file = File::open(path).await?;
+                file.seek(SeekFrom::Start(start)).await?;
+                Ok(Box::pin(file.take(len)))
+            }
+            Self::Member { archive, name } => {
+                let (archive, name) = (archive.clone(), name.clone());
+                Ok(blocking_reader(move |writer| {
+                    archive::copy(archive, name.as_str(), start, len, writer)
+                }))
+            }
+        }
+    }
+}
+
+/// Streams what `write` produces on a blocking thread, for content only synchronous code can
+/// generate. Errors end the stream early and are only logged, as the response is already sent.
+pub fn blocking_reader(
+    write: impl FnOnce(SyncIoBridge<DuplexStream>) -> Result<()> + Send + 'static,
+) -> Reader {
+    let (writer, reader) = tokio::io::duplex(PIPE_SIZE);
+    let writer = SyncIoBridge::new(writer);
+    spawn_blocking(move || {
+        if let Err(err) = write(writer) {
+            debug!("Stopped streaming: {}", err);
+        }
+    });
+    Box::pin(reader)
+}
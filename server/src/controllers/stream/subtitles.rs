@@ -0,0 +1,27 @@
+use axum::{
+    extract::Path,
+    response::{AppendHeaders, IntoResponse, Response},
+};
+use http::{header::CONTENT_TYPE, StatusCode};
+
+use super::internal_error;
+use crate::models::entry::Entry;
+
+pub async fn show(Path((id, lang)): Path<(String, String)>) -> Result<Response, Response> {
+    let subtitle = Entry::find(&id)
+        .and_then(|entry| {
+            entry
+                .subtitles
+                .into_iter()
+                .find(|subtitle| subtitle.lang == lang)
+        })
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let bytes = tokio::fs::read(subtitle.path.as_str())
+        .await
+        .map_err(internal_error)?;
+    Ok((
+        AppendHeaders([(CONTENT_TYPE, "text/vtt; charset=utf-8")]),
+        subtitle.to_webvtt(bytes.as_slice()),
+    )
+        .into_response())
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, Query},
+    response::{IntoResponse, Response},
+};
+use http::{HeaderMap, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{cached_response, internal_error, orientation};
+use crate::{
+    models::entry::Entry,
+    utils::{
+        cache,
+        imaging::{self, ImageFormat},
+    },
+};
+
+const DEFAULT_SIZE: u32 = 256;
+
+#[derive(Deserialize, Serialize)]
+pub struct ShowArgs {
+    size: Option<u32>,
+    #[serde(default)]
+    format: ImageFormat,
+}
+
+pub async fn show(
+    method: Method,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<ShowArgs>,
+) -> Result<Response, Response> {
+    let entry = Entry::find(&id).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    if !entry.mime.starts_with("image/") {
+        return Err((StatusCode::NOT_FOUND, "Entry has no thumbnail").into_response());
+    }
+    if !imaging::is_decodable(&entry.mime) {
+        return Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Entry cannot be decoded into a thumbnail",
+        )
+            .into_response());
+    }
+    let size = query
+        .size
+        .unwrap_or(DEFAULT_SIZE)
+        .clamp(1, imaging::MAX_SIZE);
+    let orientation = orientation(&entry);
+    let key = cache::key((
+        entry.meta.location(),
+        entry.meta.updated_at,
+        size,
+        query.format,
+        orientation,
+    ));
+    let meta = entry.meta.clone();
+    let thumbnail = cache::get_or_create("thumbnails", key, move || {
+        imaging::thumbnail(&meta.read()?, size, query.format, orientation)
+    })
+    .await
+    .map_err(internal_error)?;
+    cached_response(thumbnail, query.format.mime(), &method, &headers).await
+}
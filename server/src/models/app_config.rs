@@ -1,14 +1,47 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::services::app_config;
 
+/// Largest read-ahead buffer, as up to 8 of them are kept in memory.
+pub const MAX_READ_AHEAD_SIZE: u64 = 64 * 1024 * 1024;
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct AppConfig {
     pub locale: String,
+    /// Upper bound in bytes of a single partial response, `None` to honour the full range.
+    #[serde(default = "AppConfig::default_range_chunk_size")]
+    pub range_chunk_size: Option<u64>,
+    /// Bytes read ahead of a single range request and kept in memory for the sequential requests
+    /// that follow it, `None` to always read from disk.
+    #[serde(default)]
+    pub read_ahead_size: Option<u64>,
+    /// Rotate and flip images according to their EXIF orientation before serving them.
+    #[serde(default = "AppConfig::default_auto_orient")]
+    pub auto_orient: bool,
 }
 
 impl AppConfig {
+    pub fn default_range_chunk_size() -> Option<u64> {
+        Some(1_000_000)
+    }
+
+    pub fn default_auto_orient() -> bool {
+        true
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .read_ahead_size
+            .is_some_and(|size| size > MAX_READ_AHEAD_SIZE)
+        {
+            return Err(anyhow!(
+                "Read-ahead size must be at most {MAX_READ_AHEAD_SIZE} bytes"
+            ));
+        }
+        Ok(())
+    }
+
     pub fn all() -> AppConfig {
         app_config::all()
     }
@@ -17,3 +50,24 @@ impl AppConfig {
         app_config::save(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let app_config = AppConfig {
+            locale: "en-US".to_string(),
+            range_chunk_size: AppConfig::default_range_chunk_size(),
+            read_ahead_size: Some(MAX_READ_AHEAD_SIZE),
+            auto_orient: true,
+        };
+        assert!(app_config.validate().is_ok());
+        let app_config = AppConfig {
+            read_ahead_size: Some(MAX_READ_AHEAD_SIZE + 1),
+            ..app_config
+        };
+        assert!(app_config.validate().is_err());
+    }
+}
@@ -3,27 +3,65 @@ use flp_rusty_model::RustyModel;
 use serde::{Deserialize, Serialize};
 
 use super::group::Group;
-use crate::utils::meta::Meta;
+use crate::utils::{
+    archive,
+    fs::{detect_file_mime, detect_mime, MimeSource},
+    magic,
+    meta::Meta,
+    subtitle::Subtitle,
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize, RustyModel)]
 #[rusty_model(service = "crate::services::entry", belongs_to = ["group"])]
 pub struct Entry {
     pub id: String,
     pub mime: String,
+    /// Whether `mime` was guessed from the extension or sniffed from the content.
+    #[serde(default)]
+    pub mime_source: MimeSource,
     pub meta: Meta,
+    #[serde(default)]
+    pub subtitles: Vec<Subtitle>,
     #[rusty_model(findable)]
     pub group_id: String,
 }
 
 impl Entry {
     pub fn new(meta: Meta, group_id: String) -> Self {
+        let detected = match meta.inner_path.as_ref() {
+            Some(name) => {
+                let header = archive::read_prefix(meta.path.as_str(), name, magic::HEADER_SIZE)
+                    .unwrap_or_else(|err| {
+                        debug!("Cannot read header of {}: {}", name, err);
+                        Vec::new()
+                    });
+                detect_mime(name, &header)
+            }
+            None => detect_file_mime(meta.path.as_str()),
+        };
+        Self::with_mime(meta, group_id, detected.unwrap_or_default())
+    }
+
+    /// Builds the entry of an archive member from the start of its content, already read along
+    /// with the other members.
+    pub fn from_archive_member(meta: Meta, group_id: String, header: &[u8]) -> Self {
+        let name = meta.inner_path.as_deref().unwrap_or(meta.path.as_str());
+        let detected = detect_mime(name, header).unwrap_or_default();
+        Self::with_mime(meta, group_id, detected)
+    }
+
+    fn with_mime(meta: Meta, group_id: String, (mime, mime_source): (String, MimeSource)) -> Self {
+        let subtitles = if mime.starts_with("video/") && meta.inner_path.is_none() {
+            Subtitle::discover(meta.path.as_str())
+        } else {
+            Vec::new()
+        };
         Self {
-            id: group_id.clone() + URL_SAFE.encode(meta.path.as_str()).as_str(),
-            mime: mime_guess::from_path(meta.path.as_str())
-                .first()
-                .map(|mime| mime.to_string())
-                .unwrap_or_default(),
+            id: group_id.clone() + URL_SAFE.encode(meta.location()).as_str(),
+            mime,
+            mime_source,
             meta,
+            subtitles,
             group_id,
         }
     }
@@ -1,9 +1,14 @@
+use anyhow::Result;
 use base64::{engine::general_purpose::URL_SAFE, Engine as _};
 use flp_rusty_model::RustyModel;
 use serde::{Deserialize, Serialize};
 
 use super::{entry::Entry, playlist::Playlist};
-use crate::utils::meta::Meta;
+use crate::utils::{
+    archive::{self, Archive},
+    fs::guess_mime,
+    meta::{Meta, IMAGE_HEADER_SIZE},
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize, RustyModel)]
 #[rusty_model(service = "crate::services::group", belongs_to = ["playlist"], has_many = ["entry"])]
@@ -22,4 +27,32 @@ impl Group {
             playlist_id,
         }
     }
+
+    /// Builds an entry for every image member when the group is an archive, in natural order.
+    pub fn archive_entries(&self) -> Result<Vec<Entry>> {
+        if !archive::is_archive(self.meta.path.as_str()) {
+            return Ok(Vec::new());
+        }
+        let mut archive = Archive::open(self.meta.path.as_str())?;
+        let members = archive.members()?;
+        Ok(members
+            .iter()
+            .filter(|member| {
+                guess_mime(member.name.as_str()).is_some_and(|mime| mime.starts_with("image/"))
+            })
+            .map(|member| {
+                let header = archive
+                    .read_prefix(&member.name, IMAGE_HEADER_SIZE)
+                    .unwrap_or_else(|err| {
+                        debug!("Cannot read header of {}: {}", member.name, err);
+                        Vec::new()
+                    });
+                Entry::from_archive_member(
+                    Meta::from_archive_member(&self.meta, member, &header),
+                    self.id.clone(),
+                    &header,
+                )
+            })
+            .collect())
+    }
 }
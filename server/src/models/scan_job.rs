@@ -0,0 +1,149 @@
+use std::{
+    ops::ControlFlow,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use parking_lot::RwLock;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::{
+    services::scan_job,
+    utils::fs::{walk_medias, ScanOptions, ScanProgress, ScanTree},
+};
+
+/// How long a job is kept after it ends, for clients to read its results, unless destroyed
+/// earlier.
+const FINISHED_JOB_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScanJobState {
+    #[serde(rename = "running")]
+    Running,
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScanJobStatus {
+    pub id: String,
+    pub state: ScanJobState,
+    #[serde(flatten)]
+    pub progress: ScanProgress,
+}
+
+/// A scan walking a directory tree in the background, whose matches can be read while it runs.
+pub struct ScanJob {
+    pub id: String,
+    root_path: PathBuf,
+    status: watch::Sender<ScanJobStatus>,
+    paths: RwLock<Vec<String>>,
+    cancelled: AtomicBool,
+}
+
+impl ScanJob {
+    /// Registers a job scanning `root_path` for files of `allowed_mimes` kept by `options`, and
+    /// starts it on the blocking thread pool. The job is forgotten some time after it ends.
+    pub fn start(
+        root_path: PathBuf,
+        allowed_mimes: Vec<String>,
+        options: ScanOptions,
+    ) -> Arc<Self> {
+        let id = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect::<String>();
+        let job = Arc::new(Self {
+            id: id.clone(),
+            root_path: root_path.clone(),
+            status: watch::Sender::new(ScanJobStatus {
+                id,
+                state: ScanJobState::Running,
+                progress: ScanProgress::default(),
+            }),
+            paths: RwLock::default(),
+            cancelled: AtomicBool::new(false),
+        });
+        scan_job::save(job.clone());
+
+        let scanning = job.clone();
+        let scan = tokio::task::spawn_blocking(move || {
+            walk_medias(root_path, &allowed_mimes, &options, |progress, matched| {
+                scanning.paths.write().extend(matched);
+                scanning
+                    .status
+                    .send_modify(|status| status.progress = progress.clone());
+                if scanning.cancelled.load(Ordering::Relaxed) {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+        });
+        let finishing = job.clone();
+        tokio::spawn(async move {
+            let result = scan.await;
+            finishing.status.send_modify(|status| match result {
+                Ok(progress) => {
+                    status.progress = progress;
+                    status.state = if finishing.cancelled.load(Ordering::Relaxed) {
+                        ScanJobState::Cancelled
+                    } else {
+                        ScanJobState::Completed
+                    };
+                }
+                Err(err) => {
+                    error!("Scan job {} failed: {}", finishing.id, err);
+                    status.progress.current_path = None;
+                    status.state = ScanJobState::Failed;
+                }
+            });
+            tokio::time::sleep(FINISHED_JOB_TTL).await;
+            scan_job::destroy(&finishing.id);
+        });
+        job
+    }
+
+    pub fn find(id: &str) -> Option<Arc<Self>> {
+        scan_job::find(id)
+    }
+
+    pub fn status(&self) -> ScanJobStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Watches the status, which changes after every visited path until the job ends.
+    pub fn subscribe(&self) -> watch::Receiver<ScanJobStatus> {
+        self.status.subscribe()
+    }
+
+    /// The matched paths from `offset`, at most `limit` of them, along with how many matched so
+    /// far.
+    pub fn paths(&self, offset: usize, limit: usize) -> (Vec<String>, usize) {
+        let paths = self.paths.read();
+        let page = paths.iter().skip(offset).take(limit).cloned().collect();
+        (page, paths.len())
+    }
+
+    /// The paths matched so far, nested under their directories.
+    pub fn tree(&self) -> ScanTree {
+        ScanTree::new(&self.root_path, &self.paths.read())
+    }
+
+    /// Stops the scan if it is still running, and forgets the job.
+    pub fn destroy(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        scan_job::destroy(&self.id);
+    }
+}
@@ -2,4 +2,5 @@ pub mod app_config;
 pub mod entry;
 pub mod group;
 pub mod playlist;
+pub mod scan_job;
 pub mod session;
@@ -20,5 +20,8 @@ fn system_locale() -> String {
 static INSTANCE: Lazy<RwLock<AppConfig>> = Lazy::new(|| {
     RwLock::new(AppConfig {
         locale: system_locale(),
+        range_chunk_size: AppConfig::default_range_chunk_size(),
+        read_ahead_size: None,
+        auto_orient: AppConfig::default_auto_orient(),
     })
 });
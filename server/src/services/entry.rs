@@ -3,7 +3,7 @@ use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use serde_json::Value;
 
-use crate::{models::entry::Entry, utils::meta::MetaCmpBy};
+use crate::{models::entry::Entry, utils::meta::SortKey};
 
 pub type SaveError = ();
 
@@ -42,10 +42,23 @@ pub fn set_json(value: Value) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn sort(by: MetaCmpBy, ascend: bool) {
-    INSTANCE
-        .write()
-        .sort_by(|a, b| a.meta.cmp_by(&b.meta, by, ascend));
+/// Sorts the entries of the group `group_id`, or all of them, leaving the others where they are.
+pub fn sort(keys: &[SortKey], group_id: Option<&str>) {
+    let mut entries = INSTANCE.write();
+    let indices = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| group_id.is_none() || group_id == Some(&entry.group_id))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut members = indices
+        .iter()
+        .map(|index| entries[*index].clone())
+        .collect::<Vec<_>>();
+    members.sort_by(|a, b| a.meta.cmp_by_keys(&b.meta, keys));
+    for (index, member) in indices.into_iter().zip(members) {
+        entries[index] = member;
+    }
 }
 
 pub fn shift(id: &str, offset: i64) -> anyhow::Result<()> {
@@ -63,3 +76,51 @@ pub fn shift(id: &str, offset: i64) -> anyhow::Result<()> {
 }
 
 static INSTANCE: Lazy<RwLock<Vec<Entry>>> = Lazy::new(RwLock::default);
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::utils::meta::{Meta, MetaCmpBy};
+
+    #[test]
+    fn sort_group() {
+        let entry = |group_id: &str, path: &str| {
+            let meta = Meta {
+                path: path.to_string(),
+                created_at: None,
+                created_at_source: None,
+                updated_at: Utc::now(),
+                orientation: None,
+                inner_path: None,
+                media: None,
+                size: 0,
+                image: None,
+                taken_at: None,
+            };
+            Entry::new(meta, group_id.to_string()).save().unwrap()
+        };
+        let entries = [
+            entry("sort_a", "/3.png"),
+            entry("sort_b", "/2.png"),
+            entry("sort_a", "/1.png"),
+            entry("sort_b", "/1.png"),
+            entry("sort_a", "/2.png"),
+        ];
+
+        let keys = [SortKey {
+            by: MetaCmpBy::Path,
+            ascend: true,
+            ignore_case: false,
+        }];
+        sort(&keys, Some("sort_a"));
+        let ids = Entry::all()
+            .into_iter()
+            .map(|entry| entry.id)
+            .filter(|id| entries.iter().any(|entry| &entry.id == id))
+            .collect::<Vec<_>>();
+        let expected = [2, 1, 4, 3, 0].map(|index| entries[index].id.clone());
+        assert_eq!(ids, expected);
+    }
+}
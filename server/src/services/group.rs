@@ -3,7 +3,7 @@ use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use serde_json::Value;
 
-use crate::{models::group::Group, utils::meta::MetaCmpBy};
+use crate::{models::group::Group, utils::meta::SortKey};
 
 pub type SaveError = ();
 
@@ -42,10 +42,23 @@ pub fn set_json(value: Value) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn sort(by: MetaCmpBy, ascend: bool) {
-    INSTANCE
-        .write()
-        .sort_by(|a, b| a.meta.cmp_by(&b.meta, by, ascend));
+/// Sorts the groups of the playlist `playlist_id`, or all of them, leaving the others where they are.
+pub fn sort(keys: &[SortKey], playlist_id: Option<&str>) {
+    let mut groups = INSTANCE.write();
+    let indices = groups
+        .iter()
+        .enumerate()
+        .filter(|(_, group)| playlist_id.is_none() || playlist_id == Some(&group.playlist_id))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut members = indices
+        .iter()
+        .map(|index| groups[*index].clone())
+        .collect::<Vec<_>>();
+    members.sort_by(|a, b| a.meta.cmp_by_keys(&b.meta, keys));
+    for (index, member) in indices.into_iter().zip(members) {
+        groups[index] = member;
+    }
 }
 
 pub fn shift(id: &str, offset: i64) -> anyhow::Result<()> {
@@ -63,3 +76,51 @@ pub fn shift(id: &str, offset: i64) -> anyhow::Result<()> {
 }
 
 static INSTANCE: Lazy<RwLock<Vec<Group>>> = Lazy::new(RwLock::default);
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::utils::meta::{Meta, MetaCmpBy};
+
+    #[test]
+    fn sort_playlist() {
+        let group = |playlist_id: &str, path: &str| {
+            let meta = Meta {
+                path: path.to_string(),
+                created_at: None,
+                created_at_source: None,
+                updated_at: Utc::now(),
+                orientation: None,
+                inner_path: None,
+                media: None,
+                size: 0,
+                image: None,
+                taken_at: None,
+            };
+            Group::new(meta, playlist_id.to_string()).save().unwrap()
+        };
+        let groups = [
+            group("sort_a", "/3"),
+            group("sort_b", "/2"),
+            group("sort_a", "/1"),
+            group("sort_b", "/1"),
+            group("sort_a", "/2"),
+        ];
+
+        let keys = [SortKey {
+            by: MetaCmpBy::Path,
+            ascend: true,
+            ignore_case: false,
+        }];
+        sort(&keys, Some("sort_a"));
+        let ids = Group::all()
+            .into_iter()
+            .map(|group| group.id)
+            .filter(|id| groups.iter().any(|group| &group.id == id))
+            .collect::<Vec<_>>();
+        let expected = [2, 1, 4, 3, 0].map(|index| groups[index].id.clone());
+        assert_eq!(ids, expected);
+    }
+}
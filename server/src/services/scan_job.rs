@@ -0,0 +1,20 @@
+use std::{collections::HashMap, sync::Arc};
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+use crate::models::scan_job::ScanJob;
+
+pub fn find(id: &str) -> Option<Arc<ScanJob>> {
+    INSTANCE.read().get(id).cloned()
+}
+
+pub fn save(job: Arc<ScanJob>) {
+    INSTANCE.write().insert(job.id.clone(), job);
+}
+
+pub fn destroy(id: &str) {
+    INSTANCE.write().remove(id);
+}
+
+static INSTANCE: Lazy<RwLock<HashMap<String, Arc<ScanJob>>>> = Lazy::new(RwLock::default);
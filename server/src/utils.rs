@@ -1,7 +1,18 @@
+pub mod archive;
+pub mod artwork;
+pub mod cache;
+pub mod container;
 pub mod fs;
+pub mod glob;
+pub mod imaging;
+pub mod magic;
 pub mod meta;
+pub mod natural;
+pub mod probe;
+pub mod subtitle;
 #[cfg(test)]
 pub mod test;
+pub mod zip_stream;
 
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
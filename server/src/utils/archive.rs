@@ -0,0 +1,150 @@
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use zip::ZipArchive;
+
+use super::natural;
+
+/// How many bytes [`read`] reserves at most before decompressing a member.
+const MAX_PREALLOCATION: u64 = 16 * 1024 * 1024;
+
+/// A file stored in an archive.
+pub struct Member {
+    pub name: String,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Uncompressed size in bytes.
+    pub size: u64,
+}
+
+/// Whether the file is an archive whose members can be browsed as entries.
+pub fn is_archive(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|extension| ["zip", "cbz"].contains(&extension.to_lowercase().as_str()))
+}
+
+/// An archive whose central directory was parsed once, to read many of its members.
+pub struct Archive(ZipArchive<BufReader<File>>);
+
+impl Archive {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self(open(path)?))
+    }
+
+    /// Lists the files of the archive in natural order of their names.
+    pub fn members(&mut self) -> Result<Vec<Member>> {
+        let archive = &mut self.0;
+        let mut members = Vec::with_capacity(archive.len());
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index)?;
+            if file.is_dir() {
+                continue;
+            }
+            members.push(Member {
+                name: file.name().to_string(),
+                updated_at: file.last_modified().and_then(|time| {
+                    NaiveDate::from_ymd_opt(
+                        time.year() as i32,
+                        time.month() as u32,
+                        time.day() as u32,
+                    )?
+                    .and_hms_opt(
+                        time.hour() as u32,
+                        time.minute() as u32,
+                        time.second() as u32,
+                    )
+                    .map(|time| time.and_utc())
+                }),
+                size: file.size(),
+            });
+        }
+        members.sort_by(|a, b| natural::cmp(a.name.as_str(), b.name.as_str()));
+        Ok(members)
+    }
+
+    /// Reads up to `len` bytes from the start of a member, e.g. to parse its headers.
+    pub fn read_prefix(&mut self, name: &str, len: u64) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.0.by_name(name)?.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+fn open(path: impl AsRef<Path>) -> Result<ZipArchive<BufReader<File>>> {
+    Ok(ZipArchive::new(BufReader::new(File::open(path)?))?)
+}
+
+/// The uncompressed size of a member.
+pub fn size(path: impl AsRef<Path>, name: &str) -> Result<u64> {
+    Ok(open(path)?.by_name(name)?.size())
+}
+
+pub fn read(path: impl AsRef<Path>, name: &str) -> Result<Vec<u8>> {
+    let mut archive = open(path)?;
+    let mut member = archive.by_name(name)?;
+    // The declared size is untrusted, so only that much is preallocated.
+    let mut bytes = Vec::with_capacity(member.size().min(MAX_PREALLOCATION) as usize);
+    member.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads up to `len` bytes from the start of a member, e.g. to parse its headers.
+pub fn read_prefix(path: impl AsRef<Path>, name: &str, len: u64) -> Result<Vec<u8>> {
+    Archive::open(path)?.read_prefix(name, len)
+}
+
+/// Decompresses `len` bytes of a member starting at `start` into `writer`.
+pub fn copy(
+    path: impl AsRef<Path>,
+    name: &str,
+    start: u64,
+    len: u64,
+    mut writer: impl Write,
+) -> Result<()> {
+    let mut archive = open(path)?;
+    let mut member = archive.by_name(name)?;
+    std::io::copy(&mut member.by_ref().take(start), &mut std::io::sink())?;
+    std::io::copy(&mut member.take(len), &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    #[test]
+    fn members() {
+        let path = fixtures_dir().join("e/1.cbz");
+        assert!(is_archive(&path));
+        assert_eq!(
+            Archive::open(&path)
+                .unwrap()
+                .members()
+                .unwrap()
+                .iter()
+                .map(|member| member.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["notes.txt", "pages/1.jpg", "pages/2.png", "pages/10.png"]
+        );
+    }
+
+    #[test]
+    fn copy() {
+        let path = fixtures_dir().join("e/1.cbz");
+        let expected = std::fs::read(fixtures_dir().join("b/1.png")).unwrap();
+        assert_eq!(size(&path, "pages/10.png").unwrap(), expected.len() as u64);
+        assert_eq!(read(&path, "pages/10.png").unwrap(), expected);
+        let mut bytes = Vec::new();
+        super::copy(&path, "pages/10.png", 10, 20, &mut bytes).unwrap();
+        assert_eq!(bytes, expected[10..30]);
+    }
+}
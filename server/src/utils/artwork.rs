@@ -0,0 +1,363 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use super::container::{
+    ebml_bytes, ebml_children, ebml_string, matroska_elements, mp4_box, mp4_path, read_bytes,
+    read_u32, syncsafe,
+};
+
+/// The "Cover (front)" picture type shared by ID3v2 and FLAC.
+const FRONT_COVER: u32 = 3;
+/// Names of sidecar artwork, by order of preference.
+const SIDECAR_NAMES: [&str; 6] = [
+    "cover.jpg",
+    "cover.jpeg",
+    "cover.png",
+    "folder.jpg",
+    "folder.jpeg",
+    "folder.png",
+];
+
+/// A picture embedded in a media file.
+#[derive(Debug, PartialEq)]
+pub struct Artwork {
+    pub mime: String,
+    pub bytes: Vec<u8>,
+}
+
+impl Artwork {
+    /// Trusts `mime` when it is an image type, and sniffs the bytes otherwise.
+    fn new(mime: &str, bytes: Vec<u8>) -> Option<Self> {
+        let mime = match mime.to_lowercase().as_str() {
+            "image/jpg" => "image/jpeg".to_string(),
+            mime if mime.starts_with("image/") => mime.to_string(),
+            _ => image::guess_format(&bytes).ok()?.to_mime_type().to_string(),
+        };
+        Some(Self { mime, bytes })
+    }
+}
+
+/// Reads the artwork embedded in an MP3 (ID3v2 `APIC`), FLAC (`PICTURE`), MP4 (`covr`) or
+/// Matroska (attachment) file, preferring the front cover when there are several pictures.
+pub fn embedded(path: impl AsRef<Path>) -> Result<Option<Artwork>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = [0; 8];
+    if reader.read_exact(&mut magic).is_err() {
+        return Ok(None);
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    if magic.starts_with(b"ID3") {
+        if let Some(artwork) = id3(&mut reader)? {
+            return Ok(Some(artwork));
+        }
+        // FLAC files sometimes carry an ID3v2 tag in front of their own metadata.
+        let mut magic = [0; 4];
+        if reader.read_exact(&mut magic).is_ok() && &magic == b"fLaC" {
+            return flac(&mut reader);
+        }
+        Ok(None)
+    } else if magic.starts_with(b"fLaC") {
+        reader.seek(SeekFrom::Start(4))?;
+        flac(&mut reader)
+    } else if &magic[4..] == b"ftyp" {
+        mp4(&mut reader)
+    } else if magic.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        matroska(&mut reader)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Finds `cover.jpg`, `folder.jpg` and the like next to a media file, ignoring case.
+pub fn sidecar(path: impl AsRef<Path>) -> Option<PathBuf> {
+    let paths = path
+        .as_ref()
+        .parent()?
+        .read_dir()
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    SIDECAR_NAMES.iter().find_map(|name| {
+        paths
+            .iter()
+            .find(|path| {
+                path.file_name()
+                    .and_then(|file_name| file_name.to_str())
+                    .is_some_and(|file_name| file_name.eq_ignore_ascii_case(name))
+            })
+            .cloned()
+    })
+}
+
+fn pick(pictures: Vec<(u32, Artwork)>) -> Option<Artwork> {
+    let index = pictures
+        .iter()
+        .position(|(picture_type, _)| *picture_type == FRONT_COVER)
+        .unwrap_or(0);
+    pictures.into_iter().nth(index).map(|(_, artwork)| artwork)
+}
+
+/// Reverts the ID3v2 unsynchronisation scheme, which inserts a zero after every `0xFF`.
+fn resynchronise(bytes: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(bytes.len());
+    for (index, byte) in bytes.iter().enumerate() {
+        if !(*byte == 0 && index > 0 && bytes[index - 1] == 0xFF) {
+            output.push(*byte);
+        }
+    }
+    output
+}
+
+/// Splits a string terminated according to an ID3v2 text encoding from what follows it.
+fn split_terminated(bytes: &[u8], encoding: u8) -> Option<(&[u8], &[u8])> {
+    if matches!(encoding, 1 | 2) {
+        let index = bytes
+            .chunks(2)
+            .position(|chunk| chunk == [0, 0])
+            .map(|index| index * 2)?;
+        Some((&bytes[..index], &bytes[index + 2..]))
+    } else {
+        let index = bytes.iter().position(|byte| *byte == 0)?;
+        Some((&bytes[..index], &bytes[index + 1..]))
+    }
+}
+
+/// Reads the pictures of an ID3v2.2, 2.3 or 2.4 tag, leaving the reader right after the tag.
+fn id3(reader: &mut (impl Read + Seek)) -> Result<Option<Artwork>> {
+    let mut header = [0; 10];
+    reader.read_exact(&mut header)?;
+    let (version, flags) = (header[3], header[5]);
+    let mut tag = read_bytes(reader, syncsafe(&header[6..10]) as u64)?;
+    if flags & 0x10 != 0 {
+        reader.seek(SeekFrom::Current(10))?;
+    }
+    if version < 4 && flags & 0x80 != 0 {
+        tag = resynchronise(&tag);
+    }
+    let mut position = 0;
+    if flags & 0x40 != 0 && tag.len() >= 4 {
+        position = match version {
+            3 => u32::from_be_bytes(tag[0..4].try_into()?) as usize + 4,
+            4 => syncsafe(&tag[0..4]) as usize,
+            _ => 0,
+        };
+    }
+
+    let (id_len, header_len) = if version == 2 { (3, 6) } else { (4, 10) };
+    let mut pictures = Vec::new();
+    while position + header_len <= tag.len() && tag[position] != 0 {
+        let header = &tag[position..position + header_len];
+        let id = &header[..id_len];
+        let size = match version {
+            2 => u32::from_be_bytes([0, header[3], header[4], header[5]]),
+            3 => u32::from_be_bytes(header[4..8].try_into()?),
+            _ => syncsafe(&header[4..8]),
+        } as usize;
+        let flags = if version == 2 { 0 } else { header[9] };
+        position += header_len;
+        let Some(frame) = tag.get(position..position + size) else {
+            break;
+        };
+        position += size;
+
+        // Skip compressed and encrypted frames.
+        let (skipped, unsynchronised, length_indicator) = match version {
+            3 => (flags & 0xC0 != 0, false, false),
+            4 => (flags & 0x0C != 0, flags & 0x02 != 0, flags & 0x01 != 0),
+            _ => (false, false, false),
+        };
+        if skipped || !(id == b"APIC" || id == b"PIC") {
+            continue;
+        }
+        let frame = if length_indicator {
+            frame.get(4..).unwrap_or_default()
+        } else {
+            frame
+        };
+        let frame = if unsynchronised {
+            resynchronise(frame)
+        } else {
+            frame.to_vec()
+        };
+        if let Some(picture) = id3_picture(&frame, version) {
+            pictures.push(picture);
+        }
+    }
+    Ok(pick(pictures))
+}
+
+fn id3_picture(frame: &[u8], version: u8) -> Option<(u32, Artwork)> {
+    let (&encoding, rest) = frame.split_first()?;
+    let (mime, rest) = if version == 2 {
+        // ID3v2.2 only has a three letter format, e.g. `JPG`, so the bytes are sniffed instead.
+        ("", rest.get(3..)?)
+    } else {
+        let (mime, rest) = split_terminated(rest, 0)?;
+        (std::str::from_utf8(mime).ok()?, rest)
+    };
+    let (&picture_type, rest) = rest.split_first()?;
+    let (_, bytes) = split_terminated(rest, encoding)?;
+    Some((picture_type as u32, Artwork::new(mime, bytes.to_vec())?))
+}
+
+/// Reads the `PICTURE` blocks of FLAC metadata, starting right after the `fLaC` marker.
+fn flac(reader: &mut (impl Read + Seek)) -> Result<Option<Artwork>> {
+    let mut pictures = Vec::new();
+    loop {
+        let header = read_u32(reader)?;
+        let (last, block_type, len) = (header >> 31 == 1, (header >> 24) & 0x7F, header & 0xFFFFFF);
+        if block_type == 6 {
+            let picture_type = read_u32(reader)?;
+            let mime_len = read_u32(reader)?;
+            let mime = String::from_utf8_lossy(&read_bytes(reader, mime_len as u64)?).to_string();
+            let description_len = read_u32(reader)?;
+            // Skip the description, then the width, height, color depth and palette size.
+            reader.seek(SeekFrom::Current(description_len as i64 + 16))?;
+            let data_len = read_u32(reader)?;
+            let bytes = read_bytes(reader, data_len as u64)?;
+            if let Some(artwork) = Artwork::new(&mime, bytes) {
+                pictures.push((picture_type, artwork));
+            }
+        } else {
+            reader.seek(SeekFrom::Current(len as i64))?;
+        }
+        if last {
+            break;
+        }
+    }
+    Ok(pick(pictures))
+}
+
+/// Reads the `covr` item of iTunes-style MP4 metadata, at `moov/udta/meta/ilst/covr`.
+fn mp4(reader: &mut (impl Read + Seek)) -> Result<Option<Artwork>> {
+    let Some(mut meta) = mp4_path(reader, &[b"moov", b"udta", b"meta"])? else {
+        return Ok(None);
+    };
+    // `meta` is a full box with version and flags, except in some QuickTime files.
+    reader.seek(SeekFrom::Start(meta.start + 4))?;
+    let mut kind = [0; 4];
+    reader.read_exact(&mut kind)?;
+    if &kind != b"hdlr" {
+        meta.start += 4;
+    }
+    let Some(ilst) = mp4_box(reader, meta.start, meta.end, b"ilst")? else {
+        return Ok(None);
+    };
+    let Some(covr) = mp4_box(reader, ilst.start, ilst.end, b"covr")? else {
+        return Ok(None);
+    };
+    let Some(data) = mp4_box(reader, covr.start, covr.end, b"data")? else {
+        return Ok(None);
+    };
+    if data.end < data.start + 8 {
+        return Ok(None);
+    }
+    reader.seek(SeekFrom::Start(data.start))?;
+    let mime = match read_u32(reader)? {
+        13 => "image/jpeg",
+        14 => "image/png",
+        27 => "image/bmp",
+        _ => "",
+    };
+    reader.seek(SeekFrom::Current(4))?;
+    let bytes = read_bytes(reader, data.end - data.start - 8)?;
+    Ok(Artwork::new(mime, bytes))
+}
+
+const EBML_ATTACHMENTS: u64 = 0x1941A469;
+const EBML_ATTACHED_FILE: u64 = 0x61A7;
+const EBML_FILE_NAME: u64 = 0x466E;
+const EBML_FILE_MIME_TYPE: u64 = 0x4660;
+const EBML_FILE_DATA: u64 = 0x465C;
+
+/// Reads the image attachments of a Matroska file, preferring the one named `cover.*`.
+fn matroska(reader: &mut (impl Read + Seek)) -> Result<Option<Artwork>> {
+    let mut pictures = Vec::new();
+    for attachments in matroska_elements(reader, &[EBML_ATTACHMENTS])? {
+        for file in ebml_children(reader, attachments.start, attachments.end)? {
+            if file.kind != EBML_ATTACHED_FILE {
+                continue;
+            }
+            let (mut name, mut mime, mut bytes) = (String::new(), String::new(), None);
+            for child in ebml_children(reader, file.start, file.end)? {
+                match child.kind {
+                    EBML_FILE_NAME => name = ebml_string(reader, &child)?,
+                    EBML_FILE_MIME_TYPE => mime = ebml_string(reader, &child)?,
+                    EBML_FILE_DATA => bytes = Some(child),
+                    _ => {}
+                }
+            }
+            let Some(bytes) = bytes.filter(|_| mime.starts_with("image/")) else {
+                continue;
+            };
+            if let Some(artwork) = Artwork::new(&mime, ebml_bytes(reader, &bytes)?) {
+                let picture_type = if name.to_lowercase().starts_with("cover.") {
+                    FRONT_COVER
+                } else {
+                    0
+                };
+                pictures.push((picture_type, artwork));
+            }
+        }
+    }
+    Ok(pick(pictures))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    #[test]
+    fn embedded() {
+        let png = std::fs::read(fixtures_dir().join("b/1.png")).unwrap();
+        let jpeg = std::fs::read(fixtures_dir().join("d/2.jpg")).unwrap();
+        for (path, mime, bytes) in [
+            ("f/a/1.mp3", "image/png", &png),
+            ("f/a/2.flac", "image/jpeg", &jpeg),
+            ("f/a/3.m4a", "image/png", &png),
+            ("f/a/4.mkv", "image/jpeg", &jpeg),
+        ] {
+            assert_eq!(
+                super::embedded(fixtures_dir().join(path)).unwrap(),
+                Some(Artwork {
+                    mime: mime.to_string(),
+                    bytes: bytes.clone(),
+                }),
+                "{path}"
+            );
+        }
+        assert_eq!(
+            super::embedded(fixtures_dir().join("f/b/1.mp3")).unwrap(),
+            None
+        );
+        assert_eq!(
+            super::embedded(fixtures_dir().join("a/a/a/1.mp4")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn sidecar() {
+        assert_eq!(
+            super::sidecar(fixtures_dir().join("f/b/1.mp3")),
+            Some(fixtures_dir().join("f/b/Folder.jpg"))
+        );
+        assert_eq!(super::sidecar(fixtures_dir().join("f/a/1.mp3")), None);
+    }
+
+    #[test]
+    fn resynchronise() {
+        assert_eq!(
+            super::resynchronise(&[0xFF, 0x00, 0xE0, 0x00, 0xFF, 0x00, 0x00]),
+            vec![0xFF, 0xE0, 0x00, 0xFF, 0x00]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+
+use super::fs::cache_path;
+
+/// Cached files older than this are removed, as what they were made from may be long gone.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Most bytes each kind of cached files may take, beyond which the oldest are removed.
+const MAX_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Least time between two evictions of the same kind, since they list the whole directory.
+const EVICTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// Derives a file name from everything the cached content depends on. The parts are hashed in
+/// their JSON form with FNV-1a, so that names stay the same across builds and toolchains.
+pub fn key(parts: impl Serialize) -> String {
+    let bytes = serde_json::to_vec(&parts).unwrap_or_default();
+    let hash = bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    });
+    format!("{hash:016x}")
+}
+
+/// Returns the path of the cached file `kind/key`, generating its content on a blocking thread
+/// first if it is not cached yet.
+pub async fn get_or_create<F>(kind: &str, key: String, create: F) -> Result<PathBuf>
+where
+    F: FnOnce() -> Result<Vec<u8>> + Send + 'static,
+{
+    let directory = cache_path().join(kind);
+    let path = directory.join(key.as_str());
+    if tokio::fs::try_exists(&path).await? {
+        return Ok(path);
+    }
+    let content = spawn_blocking(create).await??;
+    tokio::fs::create_dir_all(&directory).await?;
+    // Write to a temporary file first so that concurrent readers never see a partial file
+    let temporary = directory.join(format!("{key}.{}.tmp", rand::random::<u32>()));
+    tokio::fs::write(&temporary, content).await?;
+    tokio::fs::rename(&temporary, &path).await?;
+    if eviction_due(kind) {
+        spawn_blocking(move || {
+            if let Err(err) = evict(&directory, MAX_AGE, MAX_SIZE) {
+                error!("Cannot evict cached files: {}", err);
+            }
+        });
+    }
+    Ok(path)
+}
+
+fn eviction_due(kind: &str) -> bool {
+    let mut evictions = EVICTIONS.lock();
+    let now = Instant::now();
+    if evictions
+        .get(kind)
+        .is_some_and(|last| now.duration_since(*last) < EVICTION_INTERVAL)
+    {
+        return false;
+    }
+    evictions.insert(kind.to_string(), now);
+    true
+}
+
+/// Removes the files of `directory` older than `max_age`, then the oldest ones until the rest
+/// take at most `max_size` bytes.
+fn evict(directory: &Path, max_age: Duration, max_size: u64) -> Result<()> {
+    let now = SystemTime::now();
+    let remove = |path: &Path| {
+        if let Err(err) = std::fs::remove_file(path) {
+            debug!("Cannot remove cached file {:?}: {}", path, err);
+        }
+    };
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let modified = metadata.modified()?;
+        if now.duration_since(modified).unwrap_or_default() > max_age {
+            remove(&entry.path());
+        } else {
+            files.push((modified, metadata.len(), entry.path()));
+        }
+    }
+    files.sort();
+    let mut size = files.iter().map(|(_, len, _)| len).sum::<u64>();
+    for (_, len, path) in files {
+        if size <= max_size {
+            break;
+        }
+        remove(&path);
+        size -= len;
+    }
+    Ok(())
+}
+
+static EVICTIONS: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(Mutex::default);
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+
+    use super::*;
+
+    #[test]
+    fn stable_key() {
+        assert_eq!(key(("a", 1)), key(("a", 1)));
+        assert_ne!(key(("a", 1)), key(("a", 2)));
+        // Names must never change, or every cached file would be orphaned.
+        assert_eq!(key(("a", 1)), "14b44f969c391c53");
+    }
+
+    #[test]
+    fn eviction() {
+        let directory = std::env::temp_dir().join(format!("cache-{}", rand::random::<u32>()));
+        fs::create_dir_all(&directory).unwrap();
+        let now = SystemTime::now();
+        for (name, age) in [("old", 100), ("older", 200), ("new", 10), ("expired", 1000)] {
+            let path = directory.join(name);
+            fs::write(&path, [0; 10]).unwrap();
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(now - Duration::from_secs(age))
+                .unwrap();
+        }
+
+        evict(&directory, Duration::from_secs(500), 20).unwrap();
+        let mut names = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["new", "old"]);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,353 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use anyhow::{anyhow, Result};
+
+/// Largest value read into memory, so that corrupted sizes cannot exhaust it.
+const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+const EBML_SEGMENT: u64 = 0x18538067;
+
+/// Bit rates in kbit/s of MPEG-1 layers I to III, then MPEG-2 and 2.5 layer I, then layers II
+/// and III, by bit rate index.
+const MP3_BIT_RATES: [[u32; 15]; 5] = [
+    [
+        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+const MP3_SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+pub fn read_bytes(reader: &mut impl Read, len: u64) -> Result<Vec<u8>> {
+    if len > MAX_SIZE {
+        return Err(anyhow!("{len} bytes exceed the size limit"));
+    }
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn read_u16(reader: &mut impl Read) -> Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_be_bytes(bytes))
+}
+
+pub fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+pub fn read_u64(reader: &mut impl Read) -> Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Decodes an ID3v2 syncsafe integer, which only uses the low 7 bits of each byte.
+pub fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, byte| value << 7 | (*byte & 0x7F) as u32)
+}
+
+/// The size of the ID3v2 tag at the start of `header`, header and footer included, if any.
+pub fn id3_len(header: &[u8]) -> Option<u64> {
+    if header.len() < 10 || !header.starts_with(b"ID3") {
+        return None;
+    }
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + syncsafe(&header[6..10]) as u64 + footer)
+}
+
+/// The header of an MPEG audio frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mp3Frame {
+    /// 3 for MPEG-1, 2 for MPEG-2 and 0 for MPEG-2.5.
+    pub version: u8,
+    /// 1 to 3.
+    pub layer: u8,
+    /// In bit/s.
+    pub bit_rate: u32,
+    /// In Hz.
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub padded: bool,
+}
+
+impl Mp3Frame {
+    /// Parses the frame header at the start of `header`, rejecting reserved values and the free
+    /// format bit rate, which a random frame sync has good odds to hit.
+    pub fn parse(header: &[u8]) -> Option<Self> {
+        let [0xFF, second, third, fourth, ..] = *header else {
+            return None;
+        };
+        if second & 0xE0 != 0xE0 {
+            return None;
+        }
+        let version = (second >> 3) & 0x3;
+        let layer = 4 - ((second >> 1) & 0x3);
+        let bit_rate_index = (third >> 4) as usize;
+        let sample_rate_index = ((third >> 2) & 0x3) as usize;
+        if version == 1 || layer == 4 || bit_rate_index == 0 || bit_rate_index == 15 {
+            return None;
+        }
+        let table = match (version, layer) {
+            (3, layer) => layer as usize - 1,
+            (_, 1) => 3,
+            _ => 4,
+        };
+        let sample_rate = *MP3_SAMPLE_RATES.get(sample_rate_index)?
+            >> match version {
+                3 => 0,
+                2 => 1,
+                _ => 2,
+            };
+        Some(Self {
+            version,
+            layer,
+            bit_rate: MP3_BIT_RATES[table][bit_rate_index] * 1000,
+            sample_rate,
+            channels: if fourth >> 6 == 3 { 1 } else { 2 },
+            padded: (third >> 1) & 0x1 != 0,
+        })
+    }
+
+    /// How many samples per channel the frame holds.
+    pub fn samples(&self) -> u32 {
+        match (self.version, self.layer) {
+            (_, 1) => 384,
+            (3, _) | (_, 2) => 1152,
+            _ => 576,
+        }
+    }
+
+    /// The size of the frame in bytes, header included, where the next one starts.
+    pub fn size(&self) -> u64 {
+        // Layer I counts in slots of 4 bytes, rounding before the padding slot is added.
+        let slot = if self.layer == 1 { 4 } else { 1 };
+        let slots =
+            self.samples() as u64 / 8 / slot * self.bit_rate as u64 / self.sample_rate as u64;
+        (slots + self.padded as u64) * slot
+    }
+}
+
+/// An MP4 box or an EBML element: its type and the range of its content.
+#[derive(Clone, Copy, Debug)]
+pub struct Node<T> {
+    pub kind: T,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Lists the MP4 boxes between `start` and `end`.
+pub fn mp4_boxes(
+    reader: &mut (impl Read + Seek),
+    start: u64,
+    end: u64,
+) -> Result<Vec<Node<[u8; 4]>>> {
+    let mut boxes = Vec::new();
+    let mut position = start;
+    while end.saturating_sub(position) >= 8 {
+        reader.seek(SeekFrom::Start(position))?;
+        let size = read_u32(reader)? as u64;
+        let mut kind = [0; 4];
+        reader.read_exact(&mut kind)?;
+        let (header_len, size) = match size {
+            0 => (8, end - position),
+            1 => (16, read_u64(reader)?),
+            size => (8, size),
+        };
+        if size < header_len || size > end - position {
+            return Err(anyhow!("Invalid MP4 box size"));
+        }
+        let next = position
+            .checked_add(size)
+            .ok_or_else(|| anyhow!("Invalid MP4 box size"))?;
+        boxes.push(Node {
+            kind,
+            start: position + header_len,
+            end: next,
+        });
+        position = next;
+    }
+    Ok(boxes)
+}
+
+/// Finds the first MP4 box of type `kind` between `start` and `end`.
+pub fn mp4_box(
+    reader: &mut (impl Read + Seek),
+    start: u64,
+    end: u64,
+    kind: &[u8; 4],
+) -> Result<Option<Node<[u8; 4]>>> {
+    Ok(mp4_boxes(reader, start, end)?
+        .into_iter()
+        .find(|node| &node.kind == kind))
+}
+
+/// Follows a path of nested MP4 boxes from the start of the file, e.g. `moov/udta/meta`.
+pub fn mp4_path(
+    reader: &mut (impl Read + Seek),
+    path: &[&[u8; 4]],
+) -> Result<Option<Node<[u8; 4]>>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let mut node = Node {
+        kind: *b"root",
+        start: 0,
+        end: len,
+    };
+    for kind in path {
+        match mp4_box(reader, node.start, node.end, kind)? {
+            Some(child) => node = child,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(node))
+}
+
+/// Reads an EBML variable size integer, returning it with its marker bit, its value without it,
+/// and whether all value bits are set, which means an unknown size.
+fn ebml_vint(reader: &mut impl Read) -> Result<(u64, u64, bool)> {
+    let mut first = [0; 1];
+    reader.read_exact(&mut first)?;
+    let len = first[0].leading_zeros() as usize + 1;
+    if len > 8 {
+        return Err(anyhow!("Invalid EBML variable size integer"));
+    }
+    let mut rest = [0; 7];
+    reader.read_exact(&mut rest[..len - 1])?;
+    let raw = rest[..len - 1]
+        .iter()
+        .fold(first[0] as u64, |value, byte| value << 8 | *byte as u64);
+    let value = raw & (u64::MAX >> (64 - 7 * len));
+    Ok((raw, value, value == u64::MAX >> (64 - 7 * len)))
+}
+
+/// Reads the header of an EBML element, returning its ID, and its size unless unknown.
+fn ebml_element(reader: &mut impl Read) -> Result<(u64, Option<u64>)> {
+    let (id, _, _) = ebml_vint(reader)?;
+    let (_, size, unknown) = ebml_vint(reader)?;
+    Ok((id, (!unknown).then_some(size)))
+}
+
+/// Lists the EBML elements between `start` and `end`, which must all have a known size.
+pub fn ebml_children(
+    reader: &mut (impl Read + Seek),
+    start: u64,
+    end: u64,
+) -> Result<Vec<Node<u64>>> {
+    let mut children = Vec::new();
+    reader.seek(SeekFrom::Start(start))?;
+    while reader.stream_position()? < end {
+        let (kind, Some(size)) = ebml_element(reader)? else {
+            return Err(anyhow!("EBML element of unknown size"));
+        };
+        let start = reader.stream_position()?;
+        children.push(Node {
+            kind,
+            start,
+            end: (start + size).min(end),
+        });
+        reader.seek(SeekFrom::Start(start + size))?;
+    }
+    Ok(children)
+}
+
+/// Lists the top level elements of the Matroska segment with one of `ids`, stopping at the
+/// first element of unknown size, usually a cluster, since those can only be skipped by parsing
+/// them.
+pub fn matroska_elements(reader: &mut (impl Read + Seek), ids: &[u64]) -> Result<Vec<Node<u64>>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut elements = Vec::new();
+    let mut segment_end = None;
+    while reader.stream_position()? < segment_end.unwrap_or(len) {
+        let (kind, size) = ebml_element(reader)?;
+        let start = reader.stream_position()?;
+        match (kind, size) {
+            (EBML_SEGMENT, size) => {
+                segment_end = Some(size.map_or(len, |size| start + size));
+                continue;
+            }
+            (_, Some(size)) => {
+                if ids.contains(&kind) {
+                    elements.push(Node {
+                        kind,
+                        start,
+                        end: start + size,
+                    });
+                }
+                reader.seek(SeekFrom::Start(start + size))?;
+            }
+            // Clusters of unknown size run until the next cluster.
+            (_, None) => break,
+        }
+    }
+    Ok(elements)
+}
+
+pub fn ebml_uint(reader: &mut (impl Read + Seek), node: &Node<u64>) -> Result<u64> {
+    reader.seek(SeekFrom::Start(node.start))?;
+    let bytes = read_bytes(reader, (node.end - node.start).min(8))?;
+    Ok(bytes
+        .iter()
+        .fold(0, |value, byte| value << 8 | *byte as u64))
+}
+
+pub fn ebml_float(reader: &mut (impl Read + Seek), node: &Node<u64>) -> Result<f64> {
+    reader.seek(SeekFrom::Start(node.start))?;
+    match node.end - node.start {
+        0 => Ok(0.0),
+        4 => Ok(f32::from_bits(read_u32(reader)?) as f64),
+        8 => Ok(f64::from_bits(read_u64(reader)?)),
+        _ => Err(anyhow!("Invalid EBML float size")),
+    }
+}
+
+pub fn ebml_string(reader: &mut (impl Read + Seek), node: &Node<u64>) -> Result<String> {
+    reader.seek(SeekFrom::Start(node.start))?;
+    let bytes = read_bytes(reader, node.end - node.start)?;
+    Ok(String::from_utf8_lossy(&bytes)
+        .trim_end_matches('\0')
+        .to_string())
+}
+
+pub fn ebml_bytes(reader: &mut (impl Read + Seek), node: &Node<u64>) -> Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(node.start))?;
+    read_bytes(reader, node.end - node.start)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn invalid_mp4_boxes() {
+        let mut largesize = b"\x00\x00\x00\x01moov".to_vec();
+        largesize.extend(u64::MAX.to_be_bytes());
+        let len = largesize.len() as u64;
+        assert!(mp4_boxes(&mut Cursor::new(largesize), 0, len).is_err());
+        let overflowing = b"\x00\x00\x00\x10moov\x00\x00\x00\x00";
+        assert!(mp4_boxes(&mut Cursor::new(overflowing), 0, 12).is_err());
+        let too_small = b"\x00\x00\x00\x04moov";
+        assert!(mp4_boxes(&mut Cursor::new(too_small), 0, 8).is_err());
+        let valid = b"\x00\x00\x00\x0cmoov\x00\x00\x00\x00";
+        let boxes = mp4_boxes(&mut Cursor::new(valid), 0, 12).unwrap();
+        assert_eq!(
+            (&boxes[0].kind, boxes[0].start, boxes[0].end),
+            (b"moov", 8, 12)
+        );
+    }
+}
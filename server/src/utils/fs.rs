@@ -1,6 +1,16 @@
-use std::{env::current_exe, path::PathBuf};
+use std::{
+    env::current_exe,
+    fs::File,
+    io::Read,
+    ops::ControlFlow,
+    path::{Component, Path, PathBuf},
+};
 
-use walkdir::WalkDir;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use walkdir::{DirEntry, WalkDir};
+
+use super::{glob, magic, natural, system_time_to_utc};
 
 pub fn exe_path() -> PathBuf {
     current_exe().unwrap()
@@ -14,6 +24,82 @@ pub fn public_path() -> PathBuf {
     exe_directory().join("public")
 }
 
+pub fn cache_path() -> PathBuf {
+    exe_directory().join("cache")
+}
+
+/// Guesses the MIME type of a file from its extension, falling back to the formats known to the
+/// `image` crate for extensions `mime_guess` has no entry for, such as `.qoi`.
+pub fn guess_mime(path: impl AsRef<Path>) -> Option<String> {
+    mime_guess::from_path(path.as_ref())
+        .first()
+        .map(|mime| mime.to_string())
+        .or_else(|| {
+            image::ImageFormat::from_path(path)
+                .ok()
+                .map(|format| format.to_mime_type().to_string())
+        })
+}
+
+/// Where the MIME type of a file came from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum MimeSource {
+    /// Guessed from the extension, as the content is of no format known to [`magic::sniff`].
+    #[default]
+    #[serde(rename = "extension")]
+    Extension,
+    /// Guessed from the extension, and confirmed by the content.
+    #[serde(rename = "confirmed")]
+    Confirmed,
+    /// Sniffed from the content, as the extension is missing or tells another format.
+    #[serde(rename = "content")]
+    Content,
+}
+
+/// Guesses the MIME type of a file from its extension, and checks it against the magic bytes its
+/// content starts with in `header`, which win when they tell another format. Weak signs, see
+/// [`magic::sniff_weak`], only win over a missing or generic guess.
+pub fn detect_mime(path: impl AsRef<Path>, header: &[u8]) -> Option<(String, MimeSource)> {
+    let guessed = guess_mime(path);
+    let (sniffed, strong) = match magic::sniff(header) {
+        Some(sniffed) => (Some(sniffed), true),
+        None => (magic::sniff_weak(header), false),
+    };
+    let generic = matches!(guessed.as_deref(), None | Some("application/octet-stream"));
+    match (guessed, sniffed) {
+        (Some(guessed), Some(sniffed)) if magic::same_format(&guessed, sniffed) => {
+            Some((guessed, MimeSource::Confirmed))
+        }
+        (_, Some(sniffed)) if strong || generic => Some((sniffed.to_string(), MimeSource::Content)),
+        (guessed, _) => guessed.map(|mime| (mime, MimeSource::Extension)),
+    }
+}
+
+/// Detects the MIME type of the file at `path` from its extension and header, or from its
+/// extension alone if it cannot be read.
+pub fn detect_file_mime(path: impl AsRef<Path>) -> Option<(String, MimeSource)> {
+    match File::open(path.as_ref()) {
+        Ok(file) => detect_reader_mime(path, file),
+        Err(err) => {
+            debug!("Cannot read header of {:?}: {}", path.as_ref(), err);
+            detect_mime(path, &[])
+        }
+    }
+}
+
+/// Detects the MIME type of the file at `path` from its extension and the header read from
+/// `reader`, which is left right after the header.
+pub fn detect_reader_mime(
+    path: impl AsRef<Path>,
+    reader: impl Read,
+) -> Option<(String, MimeSource)> {
+    let mut header = Vec::new();
+    if let Err(err) = reader.take(magic::HEADER_SIZE).read_to_end(&mut header) {
+        debug!("Cannot read header of {:?}: {}", path.as_ref(), err);
+    }
+    detect_mime(path, &header)
+}
+
 pub fn match_mime(mime: impl AsRef<str>, patterns: impl AsRef<[String]>) -> bool {
     patterns
         .as_ref()
@@ -21,21 +107,431 @@ pub fn match_mime(mime: impl AsRef<str>, patterns: impl AsRef<[String]>) -> bool
         .any(|pattern| mime.as_ref().starts_with(pattern))
 }
 
-pub fn scan_medias(root_path: String, allowed_mimes: Vec<String>) -> Vec<String> {
-    WalkDir::new(root_path)
+/// How far a scan has gone.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ScanProgress {
+    pub dirs_visited: u64,
+    pub files_matched: u64,
+    /// The path being visited, until the scan ends.
+    pub current_path: Option<String>,
+}
+
+/// Which paths a scan goes through and which files it keeps, besides their MIME type.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ScanOptions {
+    /// Globs, see [`glob::matches`], of which files must match one when there are any. Globs
+    /// with a `/` are matched against paths relative to the root, others against file names.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs of the files and directories to skip, matched like `include`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Depth of the shallowest files kept, 1 being the children of the root.
+    pub min_depth: Option<usize>,
+    /// Depth of the deepest paths visited.
+    pub max_depth: Option<usize>,
+    /// Skips files and directories whose name starts with a dot.
+    #[serde(default)]
+    pub skip_hidden: bool,
+    /// Follows symbolic links, skipping those which loop back to one of their ancestors.
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// In bytes.
+    pub min_size: Option<u64>,
+    /// In bytes.
+    pub max_size: Option<u64>,
+    pub modified_since: Option<DateTime<Utc>>,
+}
+
+impl ScanOptions {
+    /// Whether to visit a path, which is always the case for the root itself.
+    fn visits(&self, root_path: &Path, entry: &DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return true;
+        }
+        let name = entry.file_name().to_string_lossy();
+        !(self.skip_hidden && name.starts_with('.')
+            || self
+                .exclude
+                .iter()
+                .any(|pattern| glob_matches(pattern, root_path, entry)))
+    }
+
+    /// Whether to keep a visited file.
+    fn keeps(&self, root_path: &Path, entry: &DirEntry) -> bool {
+        if entry.depth() < self.min_depth.unwrap_or_default()
+            || !self.include.is_empty()
+                && !self
+                    .include
+                    .iter()
+                    .any(|pattern| glob_matches(pattern, root_path, entry))
+        {
+            return false;
+        }
+        if self.min_size.is_none() && self.max_size.is_none() && self.modified_since.is_none() {
+            return true;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            return false;
+        };
+        let modified_at = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| system_time_to_utc(&modified).ok());
+        (self.min_size.unwrap_or_default()..=self.max_size.unwrap_or(u64::MAX))
+            .contains(&metadata.len())
+            && modified_at.unwrap_or(DateTime::<Utc>::MIN_UTC)
+                >= self.modified_since.unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+}
+
+fn glob_matches(pattern: &str, root_path: &Path, entry: &DirEntry) -> bool {
+    if pattern.contains('/') {
+        let relative = entry.path().strip_prefix(root_path).unwrap_or(entry.path());
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        glob::matches(pattern, &relative)
+    } else {
+        glob::matches(pattern, &entry.file_name().to_string_lossy())
+    }
+}
+
+/// Walks `root_path` for files of `allowed_mimes` kept by `options`, calling `visit` after each
+/// visited path with the progress so far and the path if it matched. The walk stops as soon as
+/// `visit` breaks.
+pub fn walk_medias(
+    root_path: impl AsRef<Path>,
+    allowed_mimes: &[String],
+    options: &ScanOptions,
+    mut visit: impl FnMut(&ScanProgress, Option<String>) -> ControlFlow<()>,
+) -> ScanProgress {
+    let root_path = root_path.as_ref();
+    let mut progress = ScanProgress::default();
+    let mut walker = WalkDir::new(root_path).follow_links(options.follow_symlinks);
+    if let Some(max_depth) = options.max_depth {
+        walker = walker.max_depth(max_depth);
+    }
+    let entries = walker
         .into_iter()
-        .filter_map(|err| err.ok())
+        .filter_entry(|entry| options.visits(root_path, entry))
         .filter_map(|entry| {
-            mime_guess::from_path(entry.path())
-                .into_iter()
-                .find_map(|guess| {
-                    let mime = guess.to_string();
-                    if match_mime(mime.as_str(), allowed_mimes.as_slice()) {
-                        entry.path().to_str().map(|s| s.to_string())
-                    } else {
-                        None
-                    }
-                })
+            entry
+                .map_err(|err| debug!("Skipping path while scanning: {}", err))
+                .ok()
+        });
+    for entry in entries {
+        if entry.file_type().is_dir() {
+            progress.dirs_visited += 1;
+        }
+        let matched = (entry.file_type().is_file() && options.keeps(root_path, &entry))
+            .then(|| scan_mime(entry.path(), allowed_mimes))
+            .flatten()
+            .filter(|(mime, _)| match_mime(mime.as_str(), allowed_mimes))
+            .and_then(|_| entry.path().to_str().map(|s| s.to_string()));
+        if matched.is_some() {
+            progress.files_matched += 1;
+        }
+        progress.current_path = Some(entry.path().to_string_lossy().to_string());
+        if visit(&progress, matched).is_break() {
+            break;
+        }
+    }
+    progress.current_path = None;
+    progress
+}
+
+/// Detects the MIME type of a scanned file, only reading its header when the extension tells no
+/// type, or one of `allowed_mimes` that the content must confirm. Files whose extension tells
+/// another type, such as `.txt`, are never opened.
+fn scan_mime(path: &Path, allowed_mimes: &[String]) -> Option<(String, MimeSource)> {
+    match guess_mime(path) {
+        Some(guessed)
+            if guessed != "application/octet-stream" && !match_mime(&guessed, allowed_mimes) =>
+        {
+            Some((guessed, MimeSource::Extension))
+        }
+        _ => detect_file_mime(path),
+    }
+}
+
+pub fn scan_medias(
+    root_path: String,
+    allowed_mimes: Vec<String>,
+    options: &ScanOptions,
+) -> Vec<String> {
+    let mut paths = Vec::new();
+    walk_medias(root_path, &allowed_mimes, options, |_, matched| {
+        paths.extend(matched);
+        ControlFlow::Continue(())
+    });
+    paths
+}
+
+/// A directory holding files matched by a scan, right in it or below it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ScanTree {
+    pub path: String,
+    /// Matched files right in the directory, which would make the entries of its group.
+    pub files: Vec<String>,
+    /// Size of `files` in bytes.
+    pub size: u64,
+    /// How many files matched in the directory and below it.
+    pub total_files: usize,
+    /// Size of the files matched in the directory and below it in bytes.
+    pub total_size: u64,
+    /// Directories below holding matched files.
+    pub children: Vec<ScanTree>,
+}
+
+impl ScanTree {
+    /// Nests `paths` matched by a scan of `root_path` under their directories, in natural order.
+    pub fn new(root_path: impl AsRef<Path>, paths: &[String]) -> Self {
+        let root_path = root_path.as_ref();
+        let mut tree = Self {
+            path: root_path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        for path in paths {
+            let Some(dir) = Path::new(path)
+                .parent()
+                .and_then(|dir| dir.strip_prefix(root_path).ok())
+            else {
+                continue;
+            };
+            let size = Path::new(path)
+                .metadata()
+                .map(|metadata| metadata.len())
+                .unwrap_or_default();
+            tree.insert(dir.components(), path, size);
+        }
+        tree.sort();
+        tree
+    }
+
+    fn insert<'a>(
+        &mut self,
+        mut components: impl Iterator<Item = Component<'a>>,
+        path: &str,
+        size: u64,
+    ) {
+        self.total_files += 1;
+        self.total_size += size;
+        let Some(component) = components.next() else {
+            self.files.push(path.to_string());
+            self.size += size;
+            return;
+        };
+        let child_path = Path::new(&self.path)
+            .join(component)
+            .to_string_lossy()
+            .to_string();
+        let index = match self
+            .children
+            .iter()
+            .position(|child| child.path == child_path)
+        {
+            Some(index) => index,
+            None => {
+                self.children.push(Self {
+                    path: child_path,
+                    ..Default::default()
+                });
+                self.children.len() - 1
+            }
+        };
+        self.children[index].insert(components, path, size);
+    }
+
+    fn sort(&mut self) {
+        self.files.sort_by(|a, b| natural::cmp(a, b));
+        self.children.sort_by(|a, b| natural::cmp(&a.path, &b.path));
+        self.children.iter_mut().for_each(Self::sort);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    fn scan(root_path: &Path, options: ScanOptions) -> Vec<String> {
+        let mut paths = scan_medias(
+            root_path.to_string_lossy().to_string(),
+            vec!["image/".to_string()],
+            &options,
+        )
+        .into_iter()
+        .map(|path| {
+            Path::new(&path)
+                .strip_prefix(root_path)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
         })
-        .collect()
+        .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn scan_options() {
+        let root_path = fixtures_dir().join("b");
+        assert_eq!(
+            scan(&root_path, ScanOptions::default()),
+            ["1.png", "a/1.png", "a/a/1.png", "a/b/1.png", "a/b/2.svg"]
+        );
+        let options = ScanOptions {
+            exclude: vec!["b".to_string()],
+            include: vec!["*.png".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(scan(&root_path, options), ["1.png", "a/1.png", "a/a/1.png"]);
+        let options = ScanOptions {
+            include: vec!["**/a/*.png".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(scan(&root_path, options), ["a/1.png", "a/a/1.png"]);
+        let options = ScanOptions {
+            min_depth: Some(2),
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(scan(&root_path, options), ["a/1.png"]);
+        let options = ScanOptions {
+            max_size: Some(0),
+            ..Default::default()
+        };
+        assert!(scan(&root_path, options).is_empty());
+        let options = ScanOptions {
+            modified_since: Some(Utc::now() + chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(scan(&root_path, options).is_empty());
+    }
+
+    #[test]
+    fn scan_tree() {
+        let root_path = fixtures_dir().join("b");
+        let paths = scan_medias(
+            root_path.to_string_lossy().to_string(),
+            vec!["image/".to_string()],
+            &ScanOptions::default(),
+        );
+        let tree = ScanTree::new(&root_path, &paths);
+        let size = |path: &str| root_path.join(path).metadata().unwrap().len();
+        let file = |path: &str| root_path.join(path).to_string_lossy().to_string();
+
+        assert_eq!(tree.path, root_path.to_string_lossy());
+        assert_eq!(tree.files, [file("1.png")]);
+        assert_eq!(tree.size, size("1.png"));
+        assert_eq!(tree.total_files, 5);
+        assert_eq!(
+            tree.total_size,
+            ["1.png", "a/1.png", "a/a/1.png", "a/b/1.png", "a/b/2.svg"]
+                .into_iter()
+                .map(size)
+                .sum::<u64>()
+        );
+        let [a] = &tree.children[..] else {
+            panic!("Unexpected children: {:?}", tree.children);
+        };
+        assert_eq!((a.files.len(), a.total_files), (1, 4));
+        let [aa, ab] = &a.children[..] else {
+            panic!("Unexpected children: {:?}", a.children);
+        };
+        assert_eq!(aa.path, file("a/a"));
+        assert_eq!(ab.files, [file("a/b/1.png"), file("a/b/2.svg")]);
+        assert_eq!(ab.size, size("a/b/1.png") + size("a/b/2.svg"));
+        assert!(ab.children.is_empty());
+    }
+
+    #[test]
+    fn detect() {
+        let png = fs::read(fixtures_dir().join("b/1.png")).unwrap();
+        let detected = |mime: &str, source| Some((mime.to_string(), source));
+        assert_eq!(
+            detect_mime("1.png", &png),
+            detected("image/png", MimeSource::Confirmed)
+        );
+        assert_eq!(
+            detect_mime("1.jpg", &png),
+            detected("image/png", MimeSource::Content)
+        );
+        assert_eq!(
+            detect_mime("1", &png),
+            detected("image/png", MimeSource::Content)
+        );
+        assert_eq!(
+            detect_mime("1.svg", b"<svg"),
+            detected("image/svg+xml", MimeSource::Extension)
+        );
+        assert_eq!(detect_mime("1", b""), None);
+
+        let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000\r\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            detect_mime("1.srt", &srt),
+            detected("application/x-subrip", MimeSource::Extension)
+        );
+        let flac = b"ID3\x04\x00\x00\x00\x00\x00\x00fLaC";
+        assert_eq!(
+            detect_mime("1.flac", flac),
+            detected("audio/flac", MimeSource::Confirmed)
+        );
+        assert_eq!(
+            detect_mime("1.mp3", flac),
+            detected("audio/flac", MimeSource::Content)
+        );
+    }
+
+    #[test]
+    fn scan_by_content() {
+        let root_path = std::env::temp_dir().join(format!("scan-{}", rand::random::<u32>()));
+        fs::create_dir_all(&root_path).unwrap();
+        fs::copy(fixtures_dir().join("b/1.png"), root_path.join("1.bin")).unwrap();
+        fs::copy(fixtures_dir().join("a/a/a/1.mp4"), root_path.join("2.png")).unwrap();
+        fs::copy(fixtures_dir().join("b/1.png"), root_path.join("3.txt")).unwrap();
+
+        let allowed_mimes = vec!["image/".to_string()];
+        let paths = scan_medias(
+            root_path.to_string_lossy().to_string(),
+            allowed_mimes,
+            &ScanOptions::default(),
+        );
+        assert_eq!(paths, [root_path.join("1.bin").to_string_lossy()]);
+
+        fs::remove_dir_all(root_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scan_hidden_and_symlinks() {
+        let root_path = std::env::temp_dir().join(format!("scan-{}", rand::random::<u32>()));
+        fs::create_dir_all(root_path.join(".hidden")).unwrap();
+        fs::create_dir_all(root_path.join("a")).unwrap();
+        fs::write(root_path.join("1.png"), []).unwrap();
+        fs::write(root_path.join(".hidden/1.png"), []).unwrap();
+        fs::write(root_path.join("a/1.png"), []).unwrap();
+        std::os::unix::fs::symlink(&root_path, root_path.join("a/loop")).unwrap();
+
+        assert_eq!(
+            scan(&root_path, ScanOptions::default()),
+            [".hidden/1.png", "1.png", "a/1.png"]
+        );
+        let options = ScanOptions {
+            skip_hidden: true,
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        assert_eq!(scan(&root_path, options), ["1.png", "a/1.png"]);
+
+        fs::remove_dir_all(root_path).unwrap();
+    }
 }
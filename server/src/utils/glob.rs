@@ -0,0 +1,131 @@
+/// Matches `path` against a glob `pattern`, in which `*` matches any run of characters but `/`,
+/// `**` any run of characters, `**/` also no directory at all, `?` any character but `/`, and
+/// `[...]` a character of a set such as `[abc]`, `[a-z]` or `[!0-9]`.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let path = path.chars().collect::<Vec<_>>();
+    let memo = vec![None; (pattern.len() + 1) * (path.len() + 1)];
+    Matcher {
+        pattern,
+        path,
+        memo,
+    }
+    .matches_from(0, 0)
+}
+
+struct Matcher {
+    pattern: Vec<char>,
+    path: Vec<char>,
+    /// Whether the pattern from an index matches the path from another, so that each pair is only
+    /// tried once and patterns with many stars take polynomial time.
+    memo: Vec<Option<bool>>,
+}
+
+impl Matcher {
+    fn matches_from(&mut self, p: usize, i: usize) -> bool {
+        let key = p * (self.path.len() + 1) + i;
+        if let Some(matched) = self.memo[key] {
+            return matched;
+        }
+        let matched = self.try_match(p, i);
+        self.memo[key] = Some(matched);
+        matched
+    }
+
+    fn try_match(&mut self, p: usize, i: usize) -> bool {
+        let len = self.path.len();
+        let next = |offset: usize| self.pattern.get(p + offset).copied();
+        match (next(0), next(1), next(2)) {
+            (None, _, _) => i == len,
+            (Some('*'), Some('*'), Some('/')) => {
+                self.matches_from(p + 3, i)
+                    || (i..len)
+                        .any(|index| self.path[index] == '/' && self.matches_from(p + 3, index + 1))
+            }
+            (Some('*'), Some('*'), _) => (i..=len).any(|index| self.matches_from(p + 2, index)),
+            (Some('*'), _, _) => {
+                let end = self.path[i..]
+                    .iter()
+                    .position(|c| *c == '/')
+                    .map_or(len, |position| i + position);
+                (i..=end).any(|index| self.matches_from(p + 1, index))
+            }
+            (Some('?'), _, _) => {
+                self.path.get(i).is_some_and(|c| *c != '/') && self.matches_from(p + 1, i + 1)
+            }
+            (Some('['), _, _) => match class_len(&self.pattern[p + 1..]) {
+                Some(class_len) => {
+                    let class = &self.pattern[p + 1..p + 1 + class_len];
+                    self.path
+                        .get(i)
+                        .is_some_and(|c| *c != '/' && in_class(class, *c))
+                        && self.matches_from(p + class_len + 2, i + 1)
+                }
+                None => self.path.get(i) == Some(&'[') && self.matches_from(p + 1, i + 1),
+            },
+            (Some(c), _, _) => self.path.get(i) == Some(&c) && self.matches_from(p + 1, i + 1),
+        }
+    }
+}
+
+/// The length of a character class up to its closing `]`, which is taken literally right after
+/// the opening `[` or `[!`.
+fn class_len(class: &[char]) -> Option<usize> {
+    let start = match class.first() {
+        Some('!' | '^') => 2,
+        _ => 1,
+    };
+    class
+        .iter()
+        .skip(start)
+        .position(|c| *c == ']')
+        .map(|index| index + start)
+}
+
+fn in_class(class: &[char], c: char) -> bool {
+    let (negated, items) = match class {
+        ['!' | '^', items @ ..] => (true, items),
+        items => (false, items),
+    };
+    let mut found = false;
+    let mut index = 0;
+    while index < items.len() {
+        if index + 2 < items.len() && items[index + 1] == '-' {
+            found |= (items[index]..=items[index + 2]).contains(&c);
+            index += 3;
+        } else {
+            found |= items[index] == c;
+            index += 1;
+        }
+    }
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(matches("*.jpg", "1.jpg"));
+        assert!(!matches("*.jpg", "a/1.jpg"));
+        assert!(matches("**/*.jpg", "1.jpg"));
+        assert!(matches("**/*.jpg", "a/b/1.jpg"));
+        assert!(matches("a/**", "a/b/1.jpg"));
+        assert!(matches("a/**/1.jpg", "a/1.jpg"));
+        assert!(!matches("a/**/1.jpg", "ab/1.jpg"));
+        assert!(matches("img??.png", "img10.png"));
+        assert!(!matches("img?.png", "img/.png"));
+        assert!(matches("[0-9].png", "7.png"));
+        assert!(!matches("[!0-9].png", "7.png"));
+        assert!(matches("[]x].png", "].png"));
+        assert!(matches("[a.png", "[a.png"));
+        assert!(matches("@eaDir", "@eaDir"));
+        assert!(!matches(".git", ".github"));
+
+        let path = "a".repeat(200);
+        assert!(!matches("*a*a*a*a*a*a*a*a*a*a*a*a*b", &path));
+        assert!(!matches("**a**a**a**a**a**a**a**a**b", &path));
+        assert!(matches("*a*a*a*a*a*a*a*a*a*a*a*a*", &path));
+    }
+}
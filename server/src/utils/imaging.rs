@@ -0,0 +1,361 @@
+use std::io::{BufRead, Cursor, Seek};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use image::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage, ImageDecoder, ImageReader,
+};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_QUALITY: u8 = 85;
+
+/// Largest width or height an image is resized to, so that requests cannot exhaust memory.
+pub const MAX_SIZE: u32 = 2048;
+
+/// Image MIME types every mainstream browser renders natively.
+const WEB_MIMES: [&str; 9] = [
+    "image/apng",
+    "image/avif",
+    "image/gif",
+    "image/jpeg",
+    "image/png",
+    "image/svg+xml",
+    "image/vnd.microsoft.icon",
+    "image/webp",
+    "image/x-icon",
+];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Hash, PartialEq, Serialize)]
+pub enum ImageFormat {
+    #[default]
+    #[serde(rename = "jpeg")]
+    Jpeg,
+    #[serde(rename = "png")]
+    Png,
+    #[serde(rename = "webp")]
+    WebP,
+}
+
+impl ImageFormat {
+    pub fn from_mime(mime: impl AsRef<str>) -> Option<Self> {
+        match mime.as_ref() {
+            "image/jpeg" => Some(Self::Jpeg),
+            "image/png" => Some(Self::Png),
+            "image/webp" => Some(Self::WebP),
+            _ => None,
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::WebP => "image/webp",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Hash, PartialEq, Serialize)]
+pub enum Fit {
+    /// Scale the image down to fit in the box, keeping its aspect ratio.
+    #[default]
+    #[serde(rename = "contain")]
+    Contain,
+    /// Scale the image to cover the box, cropping whatever overflows it.
+    #[serde(rename = "cover")]
+    Cover,
+}
+
+/// Properties of an image read from its header, without decoding its pixels.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    /// The color type of the decoded pixels, e.g. `rgb8` or `la16`.
+    pub color_type: String,
+}
+
+/// Properties of an image read from its header and EXIF, see [`read_header`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImageHeader {
+    pub info: Option<ImageInfo>,
+    pub orientation: Option<u32>,
+    pub taken_at: Option<DateTime<Utc>>,
+}
+
+/// Resizing and re-encoding applied to an image before it is streamed.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Transform {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fit: Fit,
+    pub format: ImageFormat,
+    /// JPEG quality from 1 to 100. PNG and WebP are always encoded lossless.
+    pub quality: u8,
+    /// EXIF orientation to undo before resizing.
+    pub orientation: Option<u32>,
+}
+
+impl Transform {
+    pub fn apply(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let image = orient(decode(bytes)?, self.orientation);
+        let image = match (self.width, self.height, self.fit) {
+            (None, None, _) => image,
+            (Some(width), Some(height), Fit::Cover) => {
+                // Shrinks the box to fit in the image rather than upscaling the image to fill it.
+                let scale = (image.width() as f64 / width as f64)
+                    .min(image.height() as f64 / height as f64)
+                    .min(1.0);
+                let width = ((width as f64 * scale).round() as u32).max(1);
+                let height = ((height as f64 * scale).round() as u32).max(1);
+                image.resize_to_fill(width, height, FilterType::Lanczos3)
+            }
+            (width, height, _) => {
+                let width = width.unwrap_or(u32::MAX);
+                let height = height.unwrap_or(u32::MAX);
+                if image.width() <= width && image.height() <= height {
+                    image
+                } else {
+                    image.resize(width, height, FilterType::Lanczos3)
+                }
+            }
+        };
+        encode(&image, self.format, self.quality)
+    }
+}
+
+/// Whether images of this MIME type can be decoded for resizing or conversion.
+pub fn is_decodable(mime: impl AsRef<str>) -> bool {
+    image::ImageFormat::from_mime_type(mime).is_some_and(|format| format.reading_enabled())
+}
+
+/// Whether images of this MIME type have to be converted before a browser can render them.
+pub fn needs_transcoding(mime: impl AsRef<str>) -> bool {
+    !WEB_MIMES.contains(&mime.as_ref()) && is_decodable(mime)
+}
+
+/// Reads the properties of an image from one reader, parsing its EXIF only once.
+pub fn read_header(mut reader: impl BufRead + Seek) -> ImageHeader {
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok();
+    let info = reader.rewind().ok().and_then(|_| info(&mut reader));
+    ImageHeader {
+        info,
+        orientation: exif.as_ref().and_then(orientation),
+        taken_at: exif.as_ref().and_then(taken_at),
+    }
+}
+
+/// Reads the dimensions and color type of an image, guessing its format from its leading bytes.
+fn info(reader: impl BufRead + Seek) -> Option<ImageInfo> {
+    let decoder = ImageReader::new(reader)
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()?;
+    let (width, height) = decoder.dimensions();
+    Some(ImageInfo {
+        width,
+        height,
+        color_type: format!("{:?}", decoder.color_type()).to_lowercase(),
+    })
+}
+
+/// The EXIF orientation of an image, if it has one.
+fn orientation(exif: &exif::Exif) -> Option<u32> {
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+}
+
+/// When an image was taken according to its EXIF `DateTimeOriginal`, which is in UTC only if
+/// `OffsetTimeOriginal` gives its offset, and in the camera's local time otherwise.
+fn taken_at(exif: &exif::Exif) -> Option<DateTime<Utc>> {
+    let ascii = |tag| match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+        exif::Value::Ascii(values) => values.first().cloned(),
+        _ => None,
+    };
+    let mut taken_at = exif::DateTime::from_ascii(&ascii(exif::Tag::DateTimeOriginal)?).ok()?;
+    if let Some(offset) = ascii(exif::Tag::OffsetTimeOriginal) {
+        if let Err(err) = taken_at.parse_offset(&offset) {
+            debug!("Ignoring invalid EXIF offset: {}", err);
+            taken_at.offset = None;
+        }
+    }
+    let local = NaiveDate::from_ymd_opt(
+        taken_at.year as i32,
+        taken_at.month as u32,
+        taken_at.day as u32,
+    )?
+    .and_hms_nano_opt(
+        taken_at.hour as u32,
+        taken_at.minute as u32,
+        taken_at.second as u32,
+        taken_at.nanosecond.unwrap_or_default(),
+    )?;
+    Some((local - Duration::minutes(taken_at.offset.unwrap_or_default() as i64)).and_utc())
+}
+
+/// Rotates and flips an image so that it displays upright regardless of its EXIF orientation.
+pub fn orient(image: DynamicImage, orientation: Option<u32>) -> DynamicImage {
+    match orientation {
+        Some(2) => image.fliph(),
+        Some(3) => image.rotate180(),
+        Some(4) => image.flipv(),
+        Some(5) => image.rotate90().fliph(),
+        Some(6) => image.rotate90(),
+        Some(7) => image.rotate270().fliph(),
+        Some(8) => image.rotate270(),
+        _ => image,
+    }
+}
+
+pub fn decode(bytes: &[u8]) -> Result<DynamicImage> {
+    Ok(image::load_from_memory(bytes)?)
+}
+
+pub fn encode(image: &DynamicImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>> {
+    let mut bytes = Cursor::new(Vec::new());
+    match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(
+            JpegEncoder::new_with_quality(&mut bytes, quality.clamp(1, 100)),
+        )?,
+        ImageFormat::Png => image.write_with_encoder(PngEncoder::new(&mut bytes))?,
+        ImageFormat::WebP => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut bytes))?,
+    }
+    Ok(bytes.into_inner())
+}
+
+/// Downscales the image so that it fits in a `size` x `size` square, keeping its aspect ratio.
+pub fn thumbnail(
+    bytes: &[u8],
+    size: u32,
+    format: ImageFormat,
+    orientation: Option<u32>,
+) -> Result<Vec<u8>> {
+    let image = orient(decode(bytes)?, orientation);
+    if image.width() <= size && image.height() <= size {
+        encode(&image, format, DEFAULT_QUALITY)
+    } else {
+        encode(&image.thumbnail(size, size), format, DEFAULT_QUALITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::BufReader};
+
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    #[test]
+    fn thumbnail() {
+        let bytes = std::fs::read(fixtures_dir().join("b/1.png")).unwrap();
+        let bytes = super::thumbnail(&bytes, 4, ImageFormat::Jpeg, None).unwrap();
+        let image = image::load_from_memory(bytes.as_slice()).unwrap();
+        assert!(image.width() <= 4 && image.height() <= 4);
+    }
+
+    #[test]
+    fn info() {
+        let file = File::open(fixtures_dir().join("d/2.jpg")).unwrap();
+        assert_eq!(
+            super::info(BufReader::new(file)),
+            Some(ImageInfo {
+                width: 4,
+                height: 2,
+                color_type: "rgb8".to_string(),
+            })
+        );
+        assert_eq!(super::info(Cursor::new(b"not an image")), None);
+    }
+
+    #[test]
+    fn needs_transcoding() {
+        assert!(super::needs_transcoding("image/tiff"));
+        assert!(super::needs_transcoding("image/bmp"));
+        assert!(super::needs_transcoding("image/x-qoi"));
+        assert!(!super::needs_transcoding("image/png"));
+        assert!(!super::needs_transcoding("image/svg+xml"));
+        assert!(!super::needs_transcoding("video/mp4"));
+    }
+
+    #[test]
+    fn orientation() {
+        let path = fixtures_dir().join("d/2.jpg");
+        let orientation = |path| read_header(BufReader::new(File::open(path).unwrap())).orientation;
+        assert_eq!(orientation(path.clone()), Some(6));
+        assert_eq!(orientation(fixtures_dir().join("b/1.png")), None);
+        let image = orient(decode(&std::fs::read(&path).unwrap()).unwrap(), Some(6));
+        assert_eq!((image.width(), image.height()), (2, 4));
+    }
+
+    #[test]
+    fn taken_at() {
+        let taken_at = |path| {
+            read_header(BufReader::new(
+                File::open(fixtures_dir().join(path)).unwrap(),
+            ))
+            .taken_at
+        };
+        assert_eq!(
+            taken_at("d/3.jpg"),
+            Some("2021-07-04T03:34:56Z".parse().unwrap())
+        );
+        assert_eq!(taken_at("d/2.jpg"), None);
+    }
+
+    #[test]
+    fn invalid_offset() {
+        let ascii = |tag, value: &str| exif::Field {
+            tag,
+            ifd_num: exif::In::PRIMARY,
+            value: exif::Value::Ascii(vec![value.as_bytes().to_vec()]),
+        };
+        let date = ascii(exif::Tag::DateTimeOriginal, "2021:07:04 12:34:56");
+        let offset = ascii(exif::Tag::OffsetTimeOriginal, "nowhere");
+        let mut writer = exif::experimental::Writer::new();
+        writer.push_field(&date);
+        writer.push_field(&offset);
+        let mut bytes = Cursor::new(Vec::new());
+        writer.write(&mut bytes, false).unwrap();
+        let exif = exif::Reader::new().read_raw(bytes.into_inner()).unwrap();
+        assert_eq!(
+            super::taken_at(&exif),
+            Some("2021-07-04T12:34:56Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn transform() {
+        let bytes = std::fs::read(fixtures_dir().join("b/1.png")).unwrap();
+        let transform = Transform {
+            width: Some(10),
+            height: Some(5),
+            fit: Fit::Contain,
+            format: ImageFormat::Png,
+            quality: DEFAULT_QUALITY,
+            orientation: None,
+        };
+        let image = image::load_from_memory(transform.apply(&bytes).unwrap().as_slice()).unwrap();
+        assert_eq!((image.width(), image.height()), (5, 5));
+
+        let transform = Transform {
+            fit: Fit::Cover,
+            format: ImageFormat::Jpeg,
+            ..transform
+        };
+        let image = image::load_from_memory(transform.apply(&bytes).unwrap().as_slice()).unwrap();
+        assert_eq!((image.width(), image.height()), (10, 5));
+
+        let transform = Transform {
+            width: Some(80),
+            height: Some(40),
+            ..transform
+        };
+        let image = image::load_from_memory(transform.apply(&bytes).unwrap().as_slice()).unwrap();
+        assert_eq!((image.width(), image.height()), (20, 10));
+    }
+}
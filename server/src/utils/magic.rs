@@ -0,0 +1,222 @@
+use super::container::{id3_len, Mp3Frame};
+
+/// How many leading bytes of a file [`sniff`] needs to recognize every format it knows, enough
+/// to hold two MPEG audio frames.
+pub const HEADER_SIZE: u64 = 4096;
+
+/// Formats sharing a container, whose MIME types tell the same bytes apart only by extension,
+/// such as `.m4a` and `.mp4` or `.opus` and `.ogg`.
+const FAMILIES: &[&[&str]] = &[
+    &[
+        "video/mp4",
+        "video/quicktime",
+        "video/3gpp",
+        "video/x-m4v",
+        "audio/mp4",
+        "audio/m4a",
+        "audio/x-m4a",
+        "image/avif",
+        "image/heic",
+        "image/heif",
+    ],
+    &[
+        "video/x-matroska",
+        "video/webm",
+        "audio/webm",
+        "audio/x-matroska",
+    ],
+    &["audio/ogg", "audio/opus", "video/ogg", "application/ogg"],
+    &["audio/wav", "audio/x-wav", "audio/wave", "audio/vnd.wave"],
+    &["audio/aiff", "audio/x-aiff"],
+    &["audio/flac", "audio/x-flac"],
+    &["audio/mpeg", "audio/mp3", "audio/aac", "audio/x-aac"],
+    &["image/bmp", "image/x-bmp", "image/x-ms-bmp"],
+    &["video/x-msvideo", "video/avi"],
+    &["video/x-ms-asf", "video/x-ms-wmv", "audio/x-ms-wma"],
+];
+
+/// Recognizes a common image, video or audio container from the magic bytes a file starts with.
+/// Audio following an ID3v2 tag is recognized by what follows the tag.
+pub fn sniff(header: &[u8]) -> Option<&'static str> {
+    match header {
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("image/gif"),
+        [b'B', b'M', _, _, _, _, 0, 0, 0, 0, ..] => Some("image/bmp"),
+        [b'q', b'o', b'i', b'f', ..] => Some("image/x-qoi"),
+        [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => Some("image/tiff"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, kind @ ..] => match kind {
+            [b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+            [b'W', b'A', b'V', b'E', ..] => Some("audio/wav"),
+            [b'A', b'V', b'I', b' ', ..] => Some("video/x-msvideo"),
+            _ => None,
+        },
+        [b'F', b'O', b'R', b'M', _, _, _, _, b'A', b'I', b'F', b'F' | b'C', ..] => {
+            Some("audio/aiff")
+        }
+        [_, _, _, _, b'f', b't', b'y', b'p', brand @ ..] => Some(iso_media(brand)),
+        [0x1A, 0x45, 0xDF, 0xA3, rest @ ..] => Some(if contains(rest, b"webm") {
+            "video/webm"
+        } else {
+            "video/x-matroska"
+        }),
+        [b'O', b'g', b'g', b'S', rest @ ..] => Some(if contains(rest, b"theora") {
+            "video/ogg"
+        } else {
+            "audio/ogg"
+        }),
+        [b'f', b'L', b'a', b'C', ..] => Some("audio/flac"),
+        [b'F', b'L', b'V', 0x01, ..] => Some("video/x-flv"),
+        [0x00, 0x00, 0x01, 0xBA | 0xB3, ..] => Some("video/mpeg"),
+        [0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, ..] => Some("video/x-ms-asf"),
+        [b'I', b'D', b'3', ..] => sniff(header.get(id3_len(header)? as usize..)?),
+        // ADTS frames have no layer, unlike MPEG audio frames.
+        [0xFF, second, ..] if second & 0xF6 == 0xF0 => Some("audio/aac"),
+        [0xFF, ..] if mpeg_audio(header) == Some(true) => Some("audio/mpeg"),
+        _ => None,
+    }
+}
+
+/// Recognizes MPEG audio from signs too weak to override the format an extension tells: a frame
+/// header whose next frame lies past `header`, as UTF-16 text with a byte order mark can start
+/// alike, or an ID3v2 tag followed by nothing [`sniff`] knows.
+pub fn sniff_weak(header: &[u8]) -> Option<&'static str> {
+    match id3_len(header) {
+        Some(len) => Some(
+            header
+                .get(len as usize..)
+                .and_then(sniff_weak)
+                .unwrap_or("audio/mpeg"),
+        ),
+        None => mpeg_audio(header).map(|_| "audio/mpeg"),
+    }
+}
+
+/// Whether `a` and `b` are the same format, or formats sharing a container.
+pub fn same_format(a: &str, b: &str) -> bool {
+    a == b
+        || FAMILIES
+            .iter()
+            .any(|family| family.contains(&a) && family.contains(&b))
+}
+
+/// The format of an ISO base media file, told by the major brand of its `ftyp` box.
+fn iso_media(brand: &[u8]) -> &'static str {
+    match brand {
+        [b'a', b'v', b'i', b'f' | b's', ..] => "image/avif",
+        [b'h', b'e', b'i', b'c' | b'x' | b'm' | b's', ..] => "image/heic",
+        [b'm', b'i' | b's', b'f', b'1', ..] => "image/heif",
+        [b'M', b'4', b'A' | b'B', b' ', ..] => "audio/mp4",
+        [b'q', b't', b' ', b' ', ..] => "video/quicktime",
+        [b'3', b'g', ..] => "video/3gpp",
+        _ => "video/mp4",
+    }
+}
+
+/// Whether `header` starts with an MPEG audio frame followed by another one, or ends before the
+/// next frame could be checked.
+fn mpeg_audio(header: &[u8]) -> Option<bool> {
+    let size = Mp3Frame::parse(header)?.size() as usize;
+    match header.get(size..) {
+        Some(next) if next.len() >= 4 => Mp3Frame::parse(next).map(|_| true),
+        _ => Some(false),
+    }
+}
+
+fn contains(bytes: &[u8], needle: &[u8]) -> bool {
+    bytes.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Read};
+
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    fn read_header(path: &str) -> Vec<u8> {
+        let mut header = Vec::new();
+        File::open(fixtures_dir().join(path))
+            .unwrap()
+            .take(HEADER_SIZE)
+            .read_to_end(&mut header)
+            .unwrap();
+        header
+    }
+
+    fn sniff_fixture(path: &str) -> Option<&'static str> {
+        sniff(&read_header(path))
+    }
+
+    #[test]
+    fn sniff_fixtures() {
+        assert_eq!(sniff_fixture("b/1.png"), Some("image/png"));
+        assert_eq!(sniff_fixture("d/1.bmp"), Some("image/bmp"));
+        assert_eq!(sniff_fixture("d/1.qoi"), Some("image/x-qoi"));
+        assert_eq!(sniff_fixture("d/2.jpg"), Some("image/jpeg"));
+        assert_eq!(sniff_fixture("a/a/a/1.mp4"), Some("video/mp4"));
+        assert_eq!(sniff_fixture("f/a/3.m4a"), Some("audio/mp4"));
+        assert_eq!(sniff_fixture("f/a/4.mkv"), Some("video/x-matroska"));
+        assert_eq!(sniff_fixture("g/1.mkv"), Some("video/webm"));
+        assert_eq!(sniff_fixture("g/2.wav"), Some("audio/wav"));
+        assert_eq!(sniff_fixture("g/3.flac"), Some("audio/flac"));
+        assert_eq!(sniff_fixture("g/4.opus"), Some("audio/ogg"));
+        assert_eq!(sniff_fixture("g/5.mp3"), Some("audio/mpeg"));
+        assert_eq!(sniff_fixture("g/6.mp3"), Some("audio/mpeg"));
+        assert_eq!(sniff_fixture("c/1.svg"), None);
+        assert_eq!(sniff_fixture("e/1.cbz"), None);
+    }
+
+    #[test]
+    fn sniff_headers() {
+        assert_eq!(sniff(b"GIF89a\x01\x00"), Some("image/gif"));
+        assert_eq!(sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 "), Some("image/webp"));
+        assert_eq!(
+            sniff(b"RIFF\x00\x00\x00\x00AVI LIST"),
+            Some("video/x-msvideo")
+        );
+        assert_eq!(sniff(b"\x00\x00\x00\x1cftypavif"), Some("image/avif"));
+        assert_eq!(sniff(b"\x00\x00\x00\x1cftypheic"), Some("image/heic"));
+        assert_eq!(sniff(b"\x00\x00\x00\x14ftypqt  "), Some("video/quicktime"));
+        assert_eq!(sniff(b"\xFF\xF1\x50\x80"), Some("audio/aac"));
+        assert_eq!(
+            sniff(b"ID3\x04\x00\x00\x00\x00\x00\x00fLaC"),
+            Some("audio/flac")
+        );
+        assert_eq!(sniff(b"ID3\x04\x00\x00\x00\x00\x7FfLaC"), None);
+        assert_eq!(sniff(b"BMP file"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn sniff_weak_headers() {
+        let utf16 = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000\r\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        assert_eq!(sniff(&utf16), None);
+        assert_eq!(sniff_weak(&utf16), Some("audio/mpeg"));
+        let mut frames = utf16.clone();
+        frames.resize(200, 0);
+        assert_eq!(sniff_weak(&frames), None);
+        assert_eq!(
+            sniff_weak(b"ID3\x04\x00\x00\x00\x00\x7FfLaC"),
+            Some("audio/mpeg")
+        );
+        let mp3 = read_header("g/5.mp3");
+        assert_eq!(sniff(&mp3[..200]), None);
+        assert_eq!(sniff_weak(&mp3[..200]), Some("audio/mpeg"));
+        assert_eq!(sniff_weak(b"\xFF\xFB\x00\x00"), None);
+        assert_eq!(sniff_weak(b"\xFF\xFB\x9C\x00"), None);
+    }
+
+    #[test]
+    fn same_formats() {
+        assert!(same_format("image/png", "image/png"));
+        assert!(same_format("audio/m4a", "audio/mp4"));
+        assert!(same_format("audio/opus", "audio/ogg"));
+        assert!(same_format("video/x-matroska", "video/webm"));
+        assert!(!same_format("image/png", "image/jpeg"));
+        assert!(!same_format("video/mp4", "video/webm"));
+    }
+}
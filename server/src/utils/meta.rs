@@ -1,12 +1,28 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, path::Path};
+use std::{
+    cmp::Ordering,
+    fs::{File, Metadata},
+    io::{BufReader, Cursor, Read, Seek},
+    path::Path,
+};
 
-use super::system_time_to_utc;
+use super::{
+    archive::{self, Member},
+    fs::{detect_reader_mime, guess_mime},
+    imaging::{self, ImageHeader, ImageInfo},
+    natural,
+    probe::{self, MediaInfo},
+    system_time_to_utc,
+};
+
+/// How much of an archive member is decompressed to read its image header.
+pub const IMAGE_HEADER_SIZE: u64 = 64 * 1024;
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum MetaCmpBy {
+    /// Keeps the current order of items the previous keys consider equal.
     #[serde(rename = "default")]
     Default,
     #[serde(rename = "path")]
@@ -15,52 +31,209 @@ pub enum MetaCmpBy {
     CreatedAt,
     #[serde(rename = "updated_at")]
     UpdatedAt,
+    #[serde(rename = "duration")]
+    Duration,
+    #[serde(rename = "size")]
+    Size,
+    #[serde(rename = "width")]
+    Width,
+    #[serde(rename = "height")]
+    Height,
+    #[serde(rename = "aspect_ratio")]
+    AspectRatio,
+    /// The path compared with [`natural::cmp_with`].
+    #[serde(rename = "natural_path")]
+    NaturalPath,
+    /// The last component of the path, compared with [`natural::cmp_with`].
+    #[serde(rename = "file_name")]
+    FileName,
+    /// When the photo or recording was taken, or else when its file was last modified.
+    #[serde(rename = "taken_at")]
+    TakenAt,
+    /// A random order which only depends on `seed`, so that it can be reproduced.
+    #[serde(rename = "shuffle")]
+    Shuffle { seed: u64 },
+}
+
+/// One key of a multi-key sort. Later keys only order items the earlier ones consider equal.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct SortKey {
+    pub by: MetaCmpBy,
+    pub ascend: bool,
+    #[serde(default)]
+    pub ignore_case: bool,
+}
+
+/// Which timestamp of a file stands for its creation time.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CreatedAtSource {
+    /// The birth time, which `statx` only reports on filesystems recording it.
+    #[serde(rename = "birth")]
+    Birth,
+    /// The modification time.
+    #[serde(rename = "modified")]
+    Modified,
+    /// The status change time, on Unix.
+    #[serde(rename = "changed")]
+    Changed,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Meta {
     pub path: String,
-    pub created_at: DateTime<Utc>,
+    /// The first timestamp available out of the birth, modification and status change times.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Which timestamp `created_at` is, unknown for metas saved before it was recorded.
+    #[serde(default)]
+    pub created_at_source: Option<CreatedAtSource>,
     pub updated_at: DateTime<Utc>,
+    /// Raw EXIF orientation, from 1 to 8, of image files carrying one.
+    #[serde(default)]
+    pub orientation: Option<u32>,
+    /// Name of the member inside the archive at `path`, for content stored in an archive.
+    #[serde(default)]
+    pub inner_path: Option<String>,
+    /// Stream properties of audio and video files.
+    #[serde(default)]
+    pub media: Option<MediaInfo>,
+    /// Size of the content in bytes, or 0 for directories.
+    #[serde(default)]
+    pub size: u64,
+    /// Header properties of image files.
+    #[serde(default)]
+    pub image: Option<ImageInfo>,
+    /// When the photo or recording was taken, according to its EXIF or container metadata.
+    #[serde(default)]
+    pub taken_at: Option<DateTime<Utc>>,
 }
 
 impl Meta {
     pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
         let metadata = path.as_ref().metadata()?;
+        let mut file = if metadata.is_file() {
+            Some(BufReader::new(File::open(path.as_ref())?))
+        } else {
+            None
+        };
+        let mime = match file.as_mut() {
+            Some(file) => {
+                let mime = detect_reader_mime(path.as_ref(), file.by_ref())
+                    .map(|(mime, _)| mime)
+                    .unwrap_or_default();
+                file.rewind()?;
+                mime
+            }
+            None => String::new(),
+        };
+        let created_at = created_at(&metadata);
+        let media = if mime.starts_with("audio/") || mime.starts_with("video/") {
+            probe::probe(path.as_ref()).unwrap_or_else(|err| {
+                debug!("Cannot probe media: {}", err);
+                None
+            })
+        } else {
+            None
+        };
+        let header = match file {
+            Some(file) if mime.starts_with("image/") => imaging::read_header(file),
+            _ => ImageHeader::default(),
+        };
         Ok(Self {
             path: path
                 .as_ref()
                 .to_str()
                 .ok_or_else(|| anyhow!("Invalid path"))?
                 .to_string(),
-            created_at: system_time_to_utc(&metadata.created()?)?,
+            created_at: created_at.map(|(created_at, _)| created_at),
+            created_at_source: created_at.map(|(_, source)| source),
             updated_at: system_time_to_utc(&metadata.modified()?)?,
+            orientation: header.orientation,
+            inner_path: None,
+            taken_at: if mime.starts_with("image/") {
+                header.taken_at
+            } else {
+                media.as_ref().and_then(|media| media.creation_time)
+            },
+            media,
+            size: if metadata.is_file() {
+                metadata.len()
+            } else {
+                0
+            },
+            image: header.info,
         })
     }
 
-    pub fn cmp_by(&self, other: &Meta, by: MetaCmpBy, ascend: bool) -> Ordering {
+    /// Builds the meta of an archive member from up to [`IMAGE_HEADER_SIZE`] bytes of its content,
+    /// read by the caller so that the archive is opened once for all its members.
+    pub fn from_archive_member(archive: &Meta, member: &Member, header: &[u8]) -> Self {
+        let header =
+            if guess_mime(member.name.as_str()).is_some_and(|mime| mime.starts_with("image/")) {
+                imaging::read_header(Cursor::new(header))
+            } else {
+                ImageHeader::default()
+            };
+        Self {
+            path: archive.path.clone(),
+            created_at: archive.created_at,
+            created_at_source: archive.created_at_source,
+            updated_at: member.updated_at.unwrap_or(archive.updated_at),
+            orientation: header.orientation,
+            inner_path: Some(member.name.clone()),
+            media: None,
+            size: member.size,
+            image: header.info,
+            taken_at: header.taken_at,
+        }
+    }
+
+    /// Where the content lives, with archive members written as `archive!/member`.
+    pub fn location(&self) -> String {
+        match self.inner_path.as_ref() {
+            Some(inner_path) => format!("{}!/{}", self.path, inner_path),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Reads the whole content, decompressing it first for archive members.
+    pub fn read(&self) -> Result<Vec<u8>> {
+        match self.inner_path.as_ref() {
+            Some(inner_path) => archive::read(self.path.as_str(), inner_path.as_str()),
+            None => Ok(std::fs::read(self.path.as_str())?),
+        }
+    }
+
+    /// The last component of where the content lives, i.e. of the member name for archive
+    /// members.
+    pub fn file_name(&self) -> String {
+        let location = self.inner_path.as_ref().unwrap_or(&self.path);
+        Path::new(location)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| location.clone())
+    }
+
+    /// Compares by `by`, ignoring case in the natural orderings when `ignore_case` is set.
+    pub fn cmp_by(&self, other: &Meta, by: MetaCmpBy, ascend: bool, ignore_case: bool) -> Ordering {
         match by {
-            MetaCmpBy::Default => {
-                if ascend {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            }
+            MetaCmpBy::Default => Ordering::Equal,
             MetaCmpBy::Path => {
                 if ascend {
-                    self.path.cmp(&other.path)
+                    (&self.path, &self.inner_path).cmp(&(&other.path, &other.inner_path))
                 } else {
-                    other.path.cmp(&self.path)
+                    (&other.path, &other.inner_path).cmp(&(&self.path, &self.inner_path))
                 }
             }
-            MetaCmpBy::CreatedAt => {
-                if ascend {
-                    self.created_at.cmp(&other.created_at)
-                } else {
-                    other.created_at.cmp(&self.created_at)
+            // Files whose creation time is unknown come last either way.
+            MetaCmpBy::CreatedAt => match (self.created_at, other.created_at) {
+                (Some(created_at), Some(other_created_at)) => {
+                    directed(created_at.cmp(&other_created_at), ascend)
                 }
-            }
+                (created_at, other_created_at) => {
+                    created_at.is_none().cmp(&other_created_at.is_none())
+                }
+            },
             MetaCmpBy::UpdatedAt => {
                 if ascend {
                     self.updated_at.cmp(&other.updated_at)
@@ -68,27 +241,189 @@ impl Meta {
                     other.updated_at.cmp(&self.updated_at)
                 }
             }
+            MetaCmpBy::Duration => directed(
+                self.duration()
+                    .partial_cmp(&other.duration())
+                    .unwrap_or(Ordering::Equal),
+                ascend,
+            ),
+            MetaCmpBy::Size => directed(self.size.cmp(&other.size), ascend),
+            MetaCmpBy::Width => directed(
+                self.dimensions()
+                    .map(|(width, _)| width)
+                    .cmp(&other.dimensions().map(|(width, _)| width)),
+                ascend,
+            ),
+            MetaCmpBy::Height => directed(
+                self.dimensions()
+                    .map(|(_, height)| height)
+                    .cmp(&other.dimensions().map(|(_, height)| height)),
+                ascend,
+            ),
+            MetaCmpBy::AspectRatio => directed(
+                self.aspect_ratio()
+                    .partial_cmp(&other.aspect_ratio())
+                    .unwrap_or(Ordering::Equal),
+                ascend,
+            ),
+            MetaCmpBy::NaturalPath => directed(
+                natural::cmp_with(&self.path, &other.path, ignore_case).then_with(|| {
+                    natural::cmp_with(
+                        self.inner_path.as_deref().unwrap_or_default(),
+                        other.inner_path.as_deref().unwrap_or_default(),
+                        ignore_case,
+                    )
+                }),
+                ascend,
+            ),
+            MetaCmpBy::FileName => directed(
+                natural::cmp_with(&self.file_name(), &other.file_name(), ignore_case),
+                ascend,
+            ),
+            MetaCmpBy::TakenAt => directed(
+                self.taken_or_updated_at().cmp(&other.taken_or_updated_at()),
+                ascend,
+            ),
+            MetaCmpBy::Shuffle { seed } => {
+                directed(self.shuffle_key(seed).cmp(&other.shuffle_key(seed)), ascend)
+            }
         }
     }
+
+    /// Compares by each of `keys` in turn, then by path, so that items equal by every key still
+    /// get an order which does not depend on how they were ordered before, unless one of the keys
+    /// is [`MetaCmpBy::Default`].
+    pub fn cmp_by_keys(&self, other: &Meta, keys: &[SortKey]) -> Ordering {
+        keys.iter()
+            .map(|key| self.cmp_by(other, key.by, key.ascend, key.ignore_case))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| {
+                if keys.iter().any(|key| matches!(key.by, MetaCmpBy::Default)) {
+                    Ordering::Equal
+                } else {
+                    (&self.path, &self.inner_path).cmp(&(&other.path, &other.inner_path))
+                }
+            })
+    }
+
+    /// A random number mixed from `seed` and the location, the same for both across runs,
+    /// platforms and versions.
+    fn shuffle_key(&self, seed: u64) -> u64 {
+        let location = crc32fast::hash(self.location().as_bytes()) as u64;
+        splitmix64(seed ^ splitmix64(location))
+    }
+
+    /// When the content was taken, falling back to its modification time, which survives copies
+    /// better than its creation time.
+    pub fn taken_or_updated_at(&self) -> DateTime<Utc> {
+        self.taken_at.unwrap_or(self.updated_at)
+    }
+
+    /// In seconds, for audio and video whose duration could be probed.
+    pub fn duration(&self) -> Option<f64> {
+        self.media.as_ref().and_then(|media| media.duration)
+    }
+
+    /// Width and height as displayed, i.e. swapped for images rotated by their EXIF orientation,
+    /// of images and videos.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        match (self.image.as_ref(), self.media.as_ref()) {
+            (Some(image), _) if (5..=8).contains(&self.orientation.unwrap_or(1)) => {
+                Some((image.height, image.width))
+            }
+            (Some(image), _) => Some((image.width, image.height)),
+            (None, Some(media)) => media.width.zip(media.height),
+            (None, None) => None,
+        }
+    }
+
+    /// Width divided by height, as displayed.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        self.dimensions()
+            .filter(|(_, height)| *height > 0)
+            .map(|(width, height)| width as f64 / height as f64)
+    }
+}
+
+/// The creation time of a file, falling back on the modification time and then on the status
+/// change time, since many filesystems and network mounts do not record birth times.
+fn created_at(metadata: &Metadata) -> Option<(DateTime<Utc>, CreatedAtSource)> {
+    let utc = |time: std::io::Result<_>| time.ok().and_then(|time| system_time_to_utc(&time).ok());
+    utc(metadata.created())
+        .map(|created_at| (created_at, CreatedAtSource::Birth))
+        .or_else(|| utc(metadata.modified()).map(|modified| (modified, CreatedAtSource::Modified)))
+        .or_else(|| changed_at(metadata).map(|changed| (changed, CreatedAtSource::Changed)))
+}
+
+#[cfg(unix)]
+fn changed_at(metadata: &Metadata) -> Option<DateTime<Utc>> {
+    use std::os::unix::fs::MetadataExt;
+
+    DateTime::from_timestamp(metadata.ctime(), metadata.ctime_nsec() as u32)
+}
+
+#[cfg(not(unix))]
+fn changed_at(_metadata: &Metadata) -> Option<DateTime<Utc>> {
+    None
+}
+
+fn directed(ordering: Ordering, ascend: bool) -> Ordering {
+    if ascend {
+        ordering
+    } else {
+        ordering.reverse()
+    }
+}
+
+/// The SplitMix64 finalizer, a fixed bijection scattering nearby inputs across the whole range.
+fn splitmix64(value: u64) -> u64 {
+    let value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test::fixtures_dir;
 
     fn meta_1() -> Meta {
         Meta {
             path: "/1/path".to_string(),
-            created_at: DateTime::<Utc>::from_timestamp_millis(2).unwrap(),
+            created_at: DateTime::<Utc>::from_timestamp_millis(2),
+            created_at_source: Some(CreatedAtSource::Birth),
             updated_at: DateTime::<Utc>::from_timestamp_millis(3).unwrap(),
+            orientation: None,
+            inner_path: None,
+            media: None,
+            size: 10,
+            image: Some(ImageInfo {
+                width: 4,
+                height: 2,
+                color_type: "rgb8".to_string(),
+            }),
+            taken_at: None,
         }
     }
 
     fn meta_2() -> Meta {
         Meta {
             path: "/2/path".to_string(),
-            created_at: DateTime::<Utc>::from_timestamp_millis(1).unwrap(),
+            created_at: DateTime::<Utc>::from_timestamp_millis(1),
+            created_at_source: Some(CreatedAtSource::Birth),
             updated_at: DateTime::<Utc>::from_timestamp_millis(4).unwrap(),
+            orientation: None,
+            inner_path: None,
+            media: Some(MediaInfo {
+                duration: Some(1.5),
+                width: Some(3),
+                height: Some(3),
+                ..Default::default()
+            }),
+            size: 20,
+            image: None,
+            taken_at: Some(DateTime::<Utc>::from_timestamp_millis(1).unwrap()),
         }
     }
 
@@ -96,13 +431,235 @@ mod tests {
     fn cmp_by() {
         let meta1 = meta_1();
         let meta2 = meta_2();
-        assert!(meta1.cmp_by(&meta2, MetaCmpBy::Default, true).is_lt());
-        assert!(meta2.cmp_by(&meta1, MetaCmpBy::Default, false).is_gt());
-        assert!(meta1.cmp_by(&meta2, MetaCmpBy::Path, true).is_lt());
-        assert!(meta2.cmp_by(&meta1, MetaCmpBy::Path, false).is_lt());
-        assert!(meta1.cmp_by(&meta2, MetaCmpBy::CreatedAt, true).is_gt());
-        assert!(meta2.cmp_by(&meta1, MetaCmpBy::CreatedAt, false).is_gt());
-        assert!(meta1.cmp_by(&meta2, MetaCmpBy::UpdatedAt, true).is_lt());
-        assert!(meta2.cmp_by(&meta1, MetaCmpBy::UpdatedAt, false).is_lt());
+        assert!(meta1
+            .cmp_by(&meta2, MetaCmpBy::Default, true, false)
+            .is_eq());
+        assert!(meta2
+            .cmp_by(&meta1, MetaCmpBy::Default, false, false)
+            .is_eq());
+        assert!(meta1.cmp_by(&meta2, MetaCmpBy::Path, true, false).is_lt());
+        assert!(meta2.cmp_by(&meta1, MetaCmpBy::Path, false, false).is_lt());
+        assert!(meta1
+            .cmp_by(&meta2, MetaCmpBy::CreatedAt, true, false)
+            .is_gt());
+        assert!(meta2
+            .cmp_by(&meta1, MetaCmpBy::CreatedAt, false, false)
+            .is_gt());
+        assert!(meta1
+            .cmp_by(&meta2, MetaCmpBy::UpdatedAt, true, false)
+            .is_lt());
+        assert!(meta2
+            .cmp_by(&meta1, MetaCmpBy::UpdatedAt, false, false)
+            .is_lt());
+        assert!(meta1
+            .cmp_by(&meta2, MetaCmpBy::Duration, true, false)
+            .is_lt());
+        assert!(meta2
+            .cmp_by(&meta1, MetaCmpBy::Duration, false, false)
+            .is_lt());
+        assert!(meta1.cmp_by(&meta2, MetaCmpBy::Size, true, false).is_lt());
+        assert!(meta2.cmp_by(&meta1, MetaCmpBy::Size, false, false).is_lt());
+        assert!(meta1.cmp_by(&meta2, MetaCmpBy::Width, true, false).is_gt());
+        assert!(meta1.cmp_by(&meta2, MetaCmpBy::Height, true, false).is_lt());
+        assert!(meta1
+            .cmp_by(&meta2, MetaCmpBy::AspectRatio, true, false)
+            .is_gt());
+        // meta2 was taken before meta1 was last modified.
+        assert!(meta1
+            .cmp_by(&meta2, MetaCmpBy::TakenAt, true, false)
+            .is_gt());
+        assert!(meta2
+            .cmp_by(&meta1, MetaCmpBy::TakenAt, false, false)
+            .is_gt());
+        assert!(meta2
+            .cmp_by(&meta1, MetaCmpBy::AspectRatio, false, false)
+            .is_gt());
+    }
+
+    #[test]
+    fn cmp_by_name() {
+        let meta = |path: &str| Meta {
+            path: path.to_string(),
+            ..meta_1()
+        };
+        let (a, b) = (meta("/B/img2.jpg"), meta("/a/img10.jpg"));
+        assert!(a.cmp_by(&b, MetaCmpBy::Path, true, false).is_lt());
+        assert!(a.cmp_by(&b, MetaCmpBy::NaturalPath, true, false).is_lt());
+        assert!(a.cmp_by(&b, MetaCmpBy::NaturalPath, true, true).is_gt());
+        assert!(a.cmp_by(&b, MetaCmpBy::NaturalPath, false, true).is_lt());
+        assert!(a.cmp_by(&b, MetaCmpBy::FileName, true, false).is_lt());
+        assert!(b.cmp_by(&a, MetaCmpBy::FileName, false, true).is_lt());
+
+        let member = |name: &str| Meta {
+            inner_path: Some(name.to_string()),
+            ..meta("/a/1.cbz")
+        };
+        assert_eq!(member("pages/p2.png").file_name(), "p2.png");
+        assert!(member("p2.png")
+            .cmp_by(&member("p10.png"), MetaCmpBy::NaturalPath, true, false)
+            .is_lt());
+    }
+
+    #[test]
+    fn cmp_by_keys() {
+        let meta = |path: &str, created_at: &str| Meta {
+            path: path.to_string(),
+            created_at: created_at.parse().ok(),
+            ..meta_1()
+        };
+        let mut metas = [
+            meta("/a/img10.jpg", "2024-01-01T00:00:00Z"),
+            meta("/a/img2.jpg", "2024-01-01T00:00:00Z"),
+            meta("/a/img1.jpg", "2023-01-01T00:00:00Z"),
+            meta("/a/img3.jpg", "2024-01-01T00:00:00Z"),
+        ];
+        let keys = [
+            SortKey {
+                by: MetaCmpBy::CreatedAt,
+                ascend: false,
+                ignore_case: false,
+            },
+            SortKey {
+                by: MetaCmpBy::NaturalPath,
+                ascend: true,
+                ignore_case: false,
+            },
+        ];
+        metas.sort_by(|a, b| a.cmp_by_keys(b, &keys));
+        let paths = metas
+            .iter()
+            .map(|meta| meta.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            ["/a/img2.jpg", "/a/img3.jpg", "/a/img10.jpg", "/a/img1.jpg"]
+        );
+
+        // Without keys breaking the tie, the path still decides.
+        metas.sort_by(|a, b| a.cmp_by_keys(b, &keys[..1]));
+        let paths = metas
+            .iter()
+            .map(|meta| meta.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            ["/a/img10.jpg", "/a/img2.jpg", "/a/img3.jpg", "/a/img1.jpg"]
+        );
+    }
+
+    #[test]
+    fn cmp_by_keys_default() {
+        let meta = |path: &str| Meta {
+            path: path.to_string(),
+            ..meta_1()
+        };
+        let mut metas = [meta("/b"), meta("/c"), meta("/a")];
+        let keys = [SortKey {
+            by: MetaCmpBy::Default,
+            ascend: true,
+            ignore_case: false,
+        }];
+        metas.sort_by(|a, b| a.cmp_by_keys(b, &keys));
+        let paths = metas
+            .iter()
+            .map(|meta| meta.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["/b", "/c", "/a"]);
+    }
+
+    #[test]
+    fn shuffle() {
+        let shuffled = |seed: u64, reverse: bool| {
+            let mut metas = (0..10)
+                .map(|index| Meta {
+                    path: format!("/{index}"),
+                    ..meta_1()
+                })
+                .collect::<Vec<_>>();
+            if reverse {
+                metas.reverse();
+            }
+            let keys = [SortKey {
+                by: MetaCmpBy::Shuffle { seed },
+                ascend: true,
+                ignore_case: false,
+            }];
+            metas.sort_by(|a, b| a.cmp_by_keys(b, &keys));
+            metas.into_iter().map(|meta| meta.path).collect::<Vec<_>>()
+        };
+        let ordered = (0..10).map(|index| format!("/{index}")).collect::<Vec<_>>();
+        assert_eq!(shuffled(1, false), shuffled(1, true));
+        assert_ne!(shuffled(1, false), ordered);
+        assert_ne!(shuffled(1, false), shuffled(2, false));
+        // The order must never change, as seeds are stored to reproduce it later.
+        assert_eq!(
+            shuffled(1, false),
+            ["/3", "/8", "/0", "/4", "/2", "/6", "/5", "/1", "/9", "/7"]
+        );
+    }
+
+    #[test]
+    fn from_archive_member() {
+        let path = fixtures_dir().join("e/1.cbz");
+        let archive = Meta::from_path(&path).unwrap();
+        let member = archive::Archive::open(&path)
+            .unwrap()
+            .members()
+            .unwrap()
+            .into_iter()
+            .find(|member| member.name == "pages/1.jpg")
+            .unwrap();
+        let header = archive::read_prefix(&path, &member.name, IMAGE_HEADER_SIZE).unwrap();
+        let meta = Meta::from_archive_member(&archive, &member, &header);
+        assert_eq!(meta.size, member.size);
+        assert!(meta.image.is_some());
+        assert_eq!(meta.orientation, Some(6));
+    }
+
+    #[test]
+    fn created_at() {
+        let meta = Meta::from_path(fixtures_dir().join("b/1.png")).unwrap();
+        match meta.created_at_source {
+            Some(CreatedAtSource::Birth) => assert!(meta.created_at.is_some()),
+            Some(CreatedAtSource::Modified) => assert_eq!(meta.created_at, Some(meta.updated_at)),
+            source => panic!("Unexpected creation time source {source:?}"),
+        }
+
+        let unknown = Meta {
+            created_at: None,
+            ..meta_1()
+        };
+        for ascend in [true, false] {
+            assert!(unknown
+                .cmp_by(&meta_1(), MetaCmpBy::CreatedAt, ascend, false)
+                .is_gt());
+            assert!(meta_2()
+                .cmp_by(&unknown, MetaCmpBy::CreatedAt, ascend, false)
+                .is_lt());
+        }
+    }
+
+    #[test]
+    fn taken_at() {
+        let photo = Meta::from_path(fixtures_dir().join("d/3.jpg")).unwrap();
+        assert_eq!(
+            photo.taken_at,
+            Some("2021-07-04T03:34:56Z".parse().unwrap())
+        );
+        let video = Meta::from_path(fixtures_dir().join("g/7.mp4")).unwrap();
+        assert_eq!(video.taken_at, photo.taken_at);
+        let untagged = Meta::from_path(fixtures_dir().join("d/2.jpg")).unwrap();
+        assert_eq!(untagged.taken_at, None);
+        assert_eq!(untagged.taken_or_updated_at(), untagged.updated_at);
+    }
+
+    #[test]
+    fn dimensions() {
+        let mut meta = meta_1();
+        assert_eq!(meta.dimensions(), Some((4, 2)));
+        assert_eq!(meta.aspect_ratio(), Some(2.0));
+        meta.orientation = Some(6);
+        assert_eq!(meta.dimensions(), Some((2, 4)));
+        assert_eq!(meta_2().dimensions(), Some((3, 3)));
     }
 }
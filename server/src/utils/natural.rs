@@ -0,0 +1,101 @@
+use std::{cmp::Ordering, iter::Peekable, str::Chars};
+
+/// Code points of the zero digit of the decimal digit blocks compared numerically: ASCII,
+/// Arabic-Indic, Extended Arabic-Indic, Devanagari, Bengali, Thai and fullwidth digits.
+const ZEROS: [u32; 7] = [0x30, 0x660, 0x6F0, 0x966, 0x9E6, 0xE50, 0xFF10];
+
+fn digit(c: char) -> Option<u32> {
+    ZEROS
+        .iter()
+        .find(|zero| (**zero..*zero + 10).contains(&(c as u32)))
+        .map(|zero| c as u32 - zero)
+}
+
+/// The value of a run of digits, without leading zeros.
+fn digits(chars: &mut Peekable<Chars>) -> Vec<u32> {
+    let mut run = Vec::new();
+    while let Some(value) = chars.peek().and_then(|c| digit(*c)) {
+        if !(run.is_empty() && value == 0) {
+            run.push(value);
+        }
+        chars.next();
+    }
+    run
+}
+
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char))
+                if digit(*a_char).is_some() && digit(*b_char).is_some() =>
+            {
+                let a_number = digits(&mut a_chars);
+                let b_number = digits(&mut b_chars);
+                let ordering = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(&b_number));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+            (Some(a_char), Some(b_char)) => {
+                let ordering = a_char.cmp(b_char);
+                if ordering.is_ne() {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Compares strings the way humans read them, with runs of digits compared by their numeric
+/// value so that `img2.jpg` sorts before `img10.jpg`, whatever script the digits are written in.
+pub fn cmp(a: &str, b: &str) -> Ordering {
+    cmp_with(a, b, false)
+}
+
+/// Like [`cmp`], optionally ignoring case. Strings that only differ by case, leading zeros or
+/// digit scripts are still told apart by comparing their code points.
+pub fn cmp_with(a: &str, b: &str, ignore_case: bool) -> Ordering {
+    let ordering = if ignore_case {
+        natural_cmp(&a.to_lowercase(), &b.to_lowercase())
+    } else {
+        natural_cmp(a, b)
+    };
+    ordering.then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_cmp() {
+        assert!(cmp("img2.jpg", "img10.jpg").is_lt());
+        assert!(cmp("img10.jpg", "img2.jpg").is_gt());
+        assert!(cmp("a/2/1.png", "a/10/1.png").is_lt());
+        assert!(cmp("img02.jpg", "img2.jpg").is_lt());
+        assert!(cmp("img2.jpg", "img2.jpg").is_eq());
+        assert!(cmp("img", "img1").is_lt());
+        assert!(cmp("a", "b").is_lt());
+        assert!(cmp("第２話", "第１０話").is_lt());
+        assert!(cmp("img٣", "img12").is_lt());
+    }
+
+    #[test]
+    fn ignore_case() {
+        assert!(cmp("B.jpg", "a.jpg").is_lt());
+        assert!(cmp_with("B.jpg", "a.jpg", true).is_gt());
+        assert!(cmp_with("Ärger", "ärger", true).is_lt());
+        assert!(cmp_with("IMG9", "img10", true).is_lt());
+        assert!(cmp_with("É2", "é10", true).is_lt());
+    }
+}
@@ -0,0 +1,597 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::container::{
+    ebml_children, ebml_float, ebml_string, ebml_uint, id3_len, matroska_elements, mp4_box,
+    mp4_boxes, mp4_path, read_bytes, read_u16, read_u32, read_u64, Mp3Frame, Node,
+};
+
+/// How far from the end of an Ogg file the last page is looked for.
+const OGG_TAIL_SIZE: u64 = 64 * 1024;
+/// How far into an MP3 file, past its ID3v2 tag, the first frame is looked for.
+const MP3_SYNC_WINDOW: u64 = 64 * 1024;
+/// Seconds from 1904-01-01, the QuickTime epoch, to the Unix epoch.
+const MP4_EPOCH_OFFSET: i64 = 2_082_844_800;
+/// Seconds from the Unix epoch to 2001-01-01, the Matroska epoch.
+const MATROSKA_EPOCH_OFFSET: i64 = 978_307_200;
+
+/// Stream properties of an audio or video file, read from its container headers.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MediaInfo {
+    /// In seconds.
+    pub duration: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// In frames per second.
+    pub frame_rate: Option<f64>,
+    pub channels: Option<u32>,
+    /// In Hz.
+    pub sample_rate: Option<u32>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    /// When the recording was made, if the container says.
+    #[serde(default)]
+    pub creation_time: Option<DateTime<Utc>>,
+}
+
+/// Probes an MP4/MOV, Matroska/WebM, MP3, FLAC, Ogg or WAV file, recognized by its leading
+/// bytes rather than its extension.
+pub fn probe(path: impl AsRef<Path>) -> Result<Option<MediaInfo>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = [0; 12];
+    if reader.read_exact(&mut magic).is_err() {
+        return Ok(None);
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    let info = if &magic[4..8] == b"ftyp" {
+        mp4(&mut reader)?
+    } else if magic.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        matroska(&mut reader)?
+    } else if magic.starts_with(b"RIFF") && &magic[8..] == b"WAVE" {
+        wav(&mut reader)?
+    } else if magic.starts_with(b"OggS") {
+        ogg(&mut reader)?
+    } else {
+        // Both MP3 and FLAC files may start with an ID3v2 tag.
+        let offset = id3_len(&magic).unwrap_or(0);
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut marker = [0; 4];
+        if reader.read_exact(&mut marker).is_err() {
+            return Ok(None);
+        }
+        if &marker == b"fLaC" {
+            flac(&mut reader)?
+        } else {
+            mp3(&mut reader, offset)?
+        }
+    };
+    Ok(info.filter(|info| *info != MediaInfo::default()))
+}
+
+fn mp4_codec(fourcc: &[u8; 4]) -> String {
+    match fourcc {
+        b"avc1" | b"avc3" => "h264".to_string(),
+        b"hvc1" | b"hev1" => "hevc".to_string(),
+        b"av01" => "av1".to_string(),
+        b"vp08" => "vp8".to_string(),
+        b"vp09" => "vp9".to_string(),
+        b"mp4v" => "mpeg4".to_string(),
+        b"mp4a" => "aac".to_string(),
+        b"Opus" => "opus".to_string(),
+        b"fLaC" => "flac".to_string(),
+        b"ac-3" => "ac3".to_string(),
+        b"ec-3" => "eac3".to_string(),
+        b".mp3" => "mp3".to_string(),
+        fourcc => String::from_utf8_lossy(fourcc).trim().to_lowercase(),
+    }
+}
+
+/// Reads the timescale and duration of an `mvhd` or `mdhd` box, whose field sizes depend on its
+/// version.
+fn mp4_timing(reader: &mut (impl Read + Seek), node: &Node<[u8; 4]>) -> Result<(u32, u64)> {
+    reader.seek(SeekFrom::Start(node.start))?;
+    let version = read_u32(reader)? >> 24;
+    if version == 1 {
+        reader.seek(SeekFrom::Current(16))?;
+        Ok((read_u32(reader)?, read_u64(reader)?))
+    } else {
+        reader.seek(SeekFrom::Current(8))?;
+        Ok((read_u32(reader)?, read_u32(reader)? as u64))
+    }
+}
+
+/// Reads the creation time of an `mvhd` box, left at zero by muxers which do not know it.
+fn mp4_creation_time(
+    reader: &mut (impl Read + Seek),
+    node: &Node<[u8; 4]>,
+) -> Result<Option<DateTime<Utc>>> {
+    reader.seek(SeekFrom::Start(node.start))?;
+    let seconds = if read_u32(reader)? >> 24 == 1 {
+        read_u64(reader)?
+    } else {
+        read_u32(reader)? as u64
+    };
+    Ok((seconds > 0)
+        .then(|| i64::try_from(seconds).ok()?.checked_sub(MP4_EPOCH_OFFSET))
+        .flatten()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0)))
+}
+
+/// Sums the sample counts of an `stts` box, whose entries must all fit in it.
+fn mp4_sample_count(reader: &mut (impl Read + Seek), stts: &Node<[u8; 4]>) -> Result<u64> {
+    reader.seek(SeekFrom::Start(stts.start + 4))?;
+    let entries = read_u32(reader)? as u64;
+    if entries > stts.end.saturating_sub(stts.start + 8) / 8 {
+        return Err(anyhow!("Invalid MP4 stts entry count"));
+    }
+    let mut samples = 0;
+    for _ in 0..entries {
+        samples += read_u32(reader)? as u64;
+        read_u32(reader)?;
+    }
+    Ok(samples)
+}
+
+fn mp4(reader: &mut (impl Read + Seek)) -> Result<Option<MediaInfo>> {
+    let Some(moov) = mp4_path(reader, &[b"moov"])? else {
+        return Ok(None);
+    };
+    let mut info = MediaInfo::default();
+    if let Some(mvhd) = mp4_box(reader, moov.start, moov.end, b"mvhd")? {
+        let (timescale, duration) = mp4_timing(reader, &mvhd)?;
+        if timescale > 0 {
+            info.duration = Some(duration as f64 / timescale as f64);
+        }
+        info.creation_time = mp4_creation_time(reader, &mvhd)?;
+    }
+    for trak in mp4_boxes(reader, moov.start, moov.end)? {
+        if &trak.kind != b"trak" {
+            continue;
+        }
+        let Some(mdia) = mp4_box(reader, trak.start, trak.end, b"mdia")? else {
+            continue;
+        };
+        let Some(hdlr) = mp4_box(reader, mdia.start, mdia.end, b"hdlr")? else {
+            continue;
+        };
+        reader.seek(SeekFrom::Start(hdlr.start + 8))?;
+        let mut handler = [0; 4];
+        reader.read_exact(&mut handler)?;
+        let (is_video, is_audio) = (&handler == b"vide", &handler == b"soun");
+        if !is_video && !is_audio {
+            continue;
+        }
+        let Some(minf) = mp4_box(reader, mdia.start, mdia.end, b"minf")? else {
+            continue;
+        };
+        let Some(stbl) = mp4_box(reader, minf.start, minf.end, b"stbl")? else {
+            continue;
+        };
+        let Some(stsd) = mp4_box(reader, stbl.start, stbl.end, b"stsd")? else {
+            continue;
+        };
+        // Skip the version, flags and entry count to reach the first sample entry.
+        let Some(entry) = mp4_boxes(reader, stsd.start + 8, stsd.end)?
+            .into_iter()
+            .next()
+        else {
+            continue;
+        };
+        // Skip the reserved bytes and data reference index of the sample entry.
+        reader.seek(SeekFrom::Start(entry.start + 8))?;
+        if is_video && info.video_codec.is_none() {
+            info.video_codec = Some(mp4_codec(&entry.kind));
+            reader.seek(SeekFrom::Current(16))?;
+            info.width = Some(read_u16(reader)? as u32);
+            info.height = Some(read_u16(reader)? as u32);
+            let mdhd = mp4_box(reader, mdia.start, mdia.end, b"mdhd")?;
+            let stts = mp4_box(reader, stbl.start, stbl.end, b"stts")?;
+            if let (Some(mdhd), Some(stts)) = (mdhd, stts) {
+                let (timescale, duration) = mp4_timing(reader, &mdhd)?;
+                let samples = mp4_sample_count(reader, &stts)?;
+                if duration > 0 {
+                    info.frame_rate = Some(samples as f64 * timescale as f64 / duration as f64);
+                }
+            }
+        } else if is_audio && info.audio_codec.is_none() {
+            info.audio_codec = Some(mp4_codec(&entry.kind));
+            reader.seek(SeekFrom::Current(8))?;
+            info.channels = Some(read_u16(reader)? as u32);
+            reader.seek(SeekFrom::Current(6))?;
+            info.sample_rate = Some(read_u32(reader)? >> 16);
+        }
+    }
+    Ok(Some(info))
+}
+
+const EBML_INFO: u64 = 0x1549A966;
+const EBML_TIMESTAMP_SCALE: u64 = 0x2AD7B1;
+const EBML_DURATION: u64 = 0x4489;
+const EBML_DATE_UTC: u64 = 0x4461;
+const EBML_TRACKS: u64 = 0x1654AE6B;
+const EBML_TRACK_ENTRY: u64 = 0xAE;
+const EBML_TRACK_TYPE: u64 = 0x83;
+const EBML_CODEC_ID: u64 = 0x86;
+const EBML_DEFAULT_DURATION: u64 = 0x23E383;
+const EBML_VIDEO: u64 = 0xE0;
+const EBML_PIXEL_WIDTH: u64 = 0xB0;
+const EBML_PIXEL_HEIGHT: u64 = 0xBA;
+const EBML_AUDIO: u64 = 0xE1;
+const EBML_SAMPLING_FREQUENCY: u64 = 0xB5;
+const EBML_CHANNELS: u64 = 0x9F;
+
+fn matroska_codec(codec_id: &str) -> String {
+    match codec_id {
+        "V_MPEG4/ISO/AVC" => "h264".to_string(),
+        "V_MPEGH/ISO/HEVC" => "hevc".to_string(),
+        "V_MPEG4/ISO/ASP" | "V_MPEG4/ISO/SP" => "mpeg4".to_string(),
+        "A_MPEG/L3" => "mp3".to_string(),
+        "A_PCM/INT/LIT" | "A_PCM/INT/BIG" => "pcm".to_string(),
+        codec_id if codec_id.starts_with("A_AAC") => "aac".to_string(),
+        codec_id => codec_id
+            .split_once('_')
+            .map_or(codec_id, |(_, codec)| codec)
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase(),
+    }
+}
+
+fn matroska(reader: &mut (impl Read + Seek)) -> Result<Option<MediaInfo>> {
+    let mut info = MediaInfo::default();
+    for element in matroska_elements(reader, &[EBML_INFO, EBML_TRACKS])? {
+        let children = ebml_children(reader, element.start, element.end)?;
+        if element.kind == EBML_INFO {
+            let mut scale = 1_000_000;
+            let mut duration = None;
+            for child in children {
+                match child.kind {
+                    EBML_TIMESTAMP_SCALE => scale = ebml_uint(reader, &child)?,
+                    EBML_DURATION => duration = Some(ebml_float(reader, &child)?),
+                    // A signed count of nanoseconds since the Matroska epoch.
+                    EBML_DATE_UTC => {
+                        let nanoseconds = ebml_uint(reader, &child)? as i64;
+                        info.creation_time = DateTime::from_timestamp(
+                            MATROSKA_EPOCH_OFFSET + nanoseconds.div_euclid(1_000_000_000),
+                            nanoseconds.rem_euclid(1_000_000_000) as u32,
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            info.duration = duration.map(|duration| duration * scale as f64 / 1e9);
+            continue;
+        }
+        for track in children {
+            if track.kind != EBML_TRACK_ENTRY {
+                continue;
+            }
+            let (mut track_type, mut codec, mut frame_duration) = (0, None, None);
+            let (mut video, mut audio) = (None, None);
+            for child in ebml_children(reader, track.start, track.end)? {
+                match child.kind {
+                    EBML_TRACK_TYPE => track_type = ebml_uint(reader, &child)?,
+                    EBML_CODEC_ID => codec = Some(matroska_codec(&ebml_string(reader, &child)?)),
+                    EBML_DEFAULT_DURATION => frame_duration = Some(ebml_uint(reader, &child)?),
+                    EBML_VIDEO => video = Some(child),
+                    EBML_AUDIO => audio = Some(child),
+                    _ => {}
+                }
+            }
+            match (track_type, video, audio) {
+                (1, Some(video), _) if info.video_codec.is_none() => {
+                    info.video_codec = codec;
+                    info.frame_rate = frame_duration
+                        .filter(|duration| *duration > 0)
+                        .map(|duration| 1e9 / duration as f64);
+                    for child in ebml_children(reader, video.start, video.end)? {
+                        match child.kind {
+                            EBML_PIXEL_WIDTH => {
+                                info.width = Some(ebml_uint(reader, &child)? as u32)
+                            }
+                            EBML_PIXEL_HEIGHT => {
+                                info.height = Some(ebml_uint(reader, &child)? as u32)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                (2, _, Some(audio)) if info.audio_codec.is_none() => {
+                    info.audio_codec = codec;
+                    // Both default to 8000 Hz mono when absent.
+                    info.sample_rate = Some(8000);
+                    info.channels = Some(1);
+                    for child in ebml_children(reader, audio.start, audio.end)? {
+                        match child.kind {
+                            EBML_SAMPLING_FREQUENCY => {
+                                info.sample_rate = Some(ebml_float(reader, &child)? as u32)
+                            }
+                            EBML_CHANNELS => {
+                                info.channels = Some(ebml_uint(reader, &child)? as u32)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(Some(info))
+}
+
+fn wav(reader: &mut (impl Read + Seek)) -> Result<Option<MediaInfo>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let mut position = 12;
+    let mut info = MediaInfo::default();
+    let mut byte_rate = 0;
+    while position + 8 <= len {
+        reader.seek(SeekFrom::Start(position))?;
+        let header = read_bytes(reader, 8)?;
+        let size = u32::from_le_bytes(header[4..8].try_into()?) as u64;
+        match &header[..4] {
+            b"fmt " => {
+                let format = read_bytes(reader, size.min(16))?;
+                if format.len() < 16 {
+                    return Err(anyhow!("Invalid WAV format chunk"));
+                }
+                info.audio_codec = Some(
+                    match u16::from_le_bytes([format[0], format[1]]) {
+                        1 | 0xFFFE => "pcm",
+                        3 => "pcm_float",
+                        6 => "alaw",
+                        7 => "mulaw",
+                        0x55 => "mp3",
+                        _ => "wav",
+                    }
+                    .to_string(),
+                );
+                info.channels = Some(u16::from_le_bytes([format[2], format[3]]) as u32);
+                info.sample_rate = Some(u32::from_le_bytes(format[4..8].try_into()?));
+                byte_rate = u32::from_le_bytes(format[8..12].try_into()?);
+            }
+            b"data" if byte_rate > 0 => {
+                // Streamed files may leave the size unset, so the file length caps it.
+                let size = size.min(len - position - 8);
+                info.duration = Some(size as f64 / byte_rate as f64);
+                break;
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size.
+        position += 8 + size + size % 2;
+    }
+    Ok(Some(info))
+}
+
+fn ogg(reader: &mut (impl Read + Seek)) -> Result<Option<MediaInfo>> {
+    let page = read_bytes(reader, 27)?;
+    let serial = &page[14..18];
+    let segments = read_bytes(reader, page[26] as u64)?;
+    let packet = read_bytes(reader, segments.iter().map(|len| *len as u64).sum())?;
+    let mut info = MediaInfo::default();
+    let (rate, pre_skip) = if packet.starts_with(b"\x01vorbis") && packet.len() >= 16 {
+        info.audio_codec = Some("vorbis".to_string());
+        info.channels = Some(packet[11] as u32);
+        let rate = u32::from_le_bytes(packet[12..16].try_into()?);
+        info.sample_rate = Some(rate);
+        (rate, 0)
+    } else if packet.starts_with(b"OpusHead") && packet.len() >= 16 {
+        info.audio_codec = Some("opus".to_string());
+        info.channels = Some(packet[9] as u32);
+        info.sample_rate = Some(u32::from_le_bytes(packet[12..16].try_into()?));
+        // Opus granule positions always count 48 kHz samples.
+        (48000, u16::from_le_bytes([packet[10], packet[11]]) as u64)
+    } else if packet.starts_with(b"\x7fFLAC") && packet.len() >= 35 {
+        // The FLAC mapping header wraps a native `fLaC` marker and STREAMINFO block.
+        return Ok(Some(flac_streaminfo(&packet[17..35])));
+    } else {
+        return Ok(None);
+    };
+
+    // The granule position of the last page of the stream is its length in samples.
+    let len = reader.seek(SeekFrom::End(0))?;
+    let start = len.saturating_sub(OGG_TAIL_SIZE);
+    reader.seek(SeekFrom::Start(start))?;
+    let tail = read_bytes(reader, len - start)?;
+    let granule = tail
+        .windows(4)
+        .enumerate()
+        .rev()
+        .filter(|(_, window)| *window == b"OggS")
+        .filter_map(|(index, _)| tail.get(index..index + 18))
+        .find(|page| &page[14..18] == serial)
+        .map(|page| i64::from_le_bytes(page[6..14].try_into().unwrap_or_default()));
+    if let Some(granule) = granule.filter(|granule| *granule >= 0) {
+        info.duration = Some((granule as u64).saturating_sub(pre_skip) as f64 / rate as f64);
+    }
+    Ok(Some(info))
+}
+
+/// Reads the 18 leading bytes of a FLAC STREAMINFO block.
+fn flac_streaminfo(block: &[u8]) -> MediaInfo {
+    let bits = u64::from_be_bytes(block[10..18].try_into().unwrap_or_default());
+    let sample_rate = (bits >> 44) as u32;
+    let channels = ((bits >> 41) & 0x7) as u32 + 1;
+    let samples = bits & 0xF_FFFF_FFFF;
+    MediaInfo {
+        duration: (sample_rate > 0 && samples > 0).then(|| samples as f64 / sample_rate as f64),
+        channels: Some(channels),
+        sample_rate: (sample_rate > 0).then_some(sample_rate),
+        audio_codec: Some("flac".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Reads the STREAMINFO block, which always comes first, right after the `fLaC` marker.
+fn flac(reader: &mut impl Read) -> Result<Option<MediaInfo>> {
+    let header = read_u32(reader)?;
+    if (header >> 24) & 0x7F != 0 {
+        return Err(anyhow!("FLAC metadata does not start with STREAMINFO"));
+    }
+    Ok(Some(flac_streaminfo(&read_bytes(reader, 18)?)))
+}
+
+/// Reads the first MPEG audio frame after `offset`, using its Xing, Info or VBRI header for the
+/// frame count of variable bit rate files, and the bit rate of constant bit rate ones.
+fn mp3(reader: &mut (impl Read + Seek), offset: u64) -> Result<Option<MediaInfo>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let window = read_bytes(reader, MP3_SYNC_WINDOW.min(len.saturating_sub(offset)))?;
+    let Some((position, frame)) = window
+        .windows(4)
+        .enumerate()
+        .find_map(|(position, header)| Some((position, Mp3Frame::parse(header)?)))
+    else {
+        return Ok(None);
+    };
+    let Mp3Frame {
+        version,
+        layer,
+        bit_rate,
+        sample_rate,
+        channels,
+        ..
+    } = frame;
+
+    // The Xing header follows the side information, whose size depends on version and mode.
+    let side_info = match (version == 3, channels) {
+        (true, 2) => 32,
+        (true, _) | (false, 2) => 17,
+        _ => 9,
+    };
+    let bytes = &window[position..];
+    let frames = if let Some(xing) = bytes
+        .get(4 + side_info..4 + side_info + 12)
+        .filter(|xing| xing.starts_with(b"Xing") || xing.starts_with(b"Info"))
+        .filter(|xing| xing[7] & 0x1 != 0)
+    {
+        Some(u32::from_be_bytes(xing[8..12].try_into()?))
+    } else {
+        bytes
+            .get(36..36 + 18)
+            .filter(|vbri| vbri.starts_with(b"VBRI"))
+            .map(|vbri| u32::from_be_bytes(vbri[14..18].try_into().unwrap_or_default()))
+    };
+    let duration = match frames {
+        Some(frames) => frames as f64 * frame.samples() as f64 / sample_rate as f64,
+        None => {
+            // An ID3v1 tag takes the last 128 bytes.
+            reader.seek(SeekFrom::Start(len.saturating_sub(128)))?;
+            let mut tag = [0; 3];
+            let tag_len = if reader.read_exact(&mut tag).is_ok() && &tag == b"TAG" {
+                128
+            } else {
+                0
+            };
+            let audio_len = len.saturating_sub(offset + position as u64 + tag_len);
+            audio_len as f64 * 8.0 / bit_rate as f64
+        }
+    };
+    Ok(Some(MediaInfo {
+        duration: Some(duration),
+        channels: Some(channels),
+        sample_rate: Some(sample_rate),
+        audio_codec: Some(format!("mp{layer}")),
+        ..Default::default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    fn probe(path: &str) -> MediaInfo {
+        super::probe(fixtures_dir().join(path)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn mp4_sample_count() {
+        let stts = |entries: u32, len: u64| {
+            let mut bytes = vec![0; 4];
+            bytes.extend(entries.to_be_bytes());
+            bytes.extend([0, 0, 0, 3, 0, 0, 0, 1].repeat(2));
+            let node = Node {
+                kind: *b"stts",
+                start: 0,
+                end: len,
+            };
+            super::mp4_sample_count(&mut std::io::Cursor::new(bytes), &node)
+        };
+        assert_eq!(stts(2, 24).unwrap(), 6);
+        assert!(stts(3, 24).is_err());
+        assert!(stts(u32::MAX, 24).is_err());
+    }
+
+    #[test]
+    fn mp4() {
+        assert_eq!(
+            probe("a/a/a/1.mp4"),
+            MediaInfo {
+                duration: Some(10.0),
+                width: Some(1920),
+                height: Some(1080),
+                frame_rate: Some(1.0),
+                video_codec: Some("h264".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn creation_time() {
+        assert_eq!(
+            probe("g/7.mp4").creation_time,
+            Some("2021-07-04T03:34:56Z".parse().unwrap())
+        );
+        assert_eq!(probe("a/a/a/1.mp4").creation_time, None);
+    }
+
+    #[test]
+    fn matroska() {
+        assert_eq!(
+            probe("g/1.mkv"),
+            MediaInfo {
+                duration: Some(12.5),
+                width: Some(640),
+                height: Some(360),
+                frame_rate: Some(25.0),
+                channels: Some(2),
+                sample_rate: Some(48000),
+                video_codec: Some("vp9".to_string()),
+                audio_codec: Some("opus".to_string()),
+                creation_time: None,
+            }
+        );
+    }
+
+    #[test]
+    fn audio() {
+        let audio = |duration, channels, sample_rate, codec: &str| MediaInfo {
+            duration: Some(duration),
+            channels: Some(channels),
+            sample_rate: Some(sample_rate),
+            audio_codec: Some(codec.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(probe("g/2.wav"), audio(0.5, 1, 8000, "pcm"));
+        assert_eq!(probe("g/3.flac"), audio(2.0, 2, 44100, "flac"));
+        assert_eq!(probe("g/4.opus"), audio(1.0, 2, 44100, "opus"));
+        assert_eq!(probe("g/5.mp3"), audio(3.0, 2, 48000, "mp3"));
+        assert_eq!(probe("g/6.mp3"), audio(1.0, 1, 44100, "mp3"));
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(super::probe(fixtures_dir().join("b/1.png")).unwrap(), None);
+    }
+}
@@ -0,0 +1,288 @@
+use std::{ffi::OsStr, path::Path};
+
+use chardetng::EncodingDetector;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{digit1, one_of, space0},
+    combinator::map_res,
+    sequence::{terminated, tuple},
+    IResult,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum SubtitleFormat {
+    #[serde(rename = "vtt")]
+    WebVtt,
+    #[serde(rename = "srt")]
+    SubRip,
+    #[serde(rename = "ass")]
+    Ass,
+}
+
+impl SubtitleFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path
+            .as_ref()
+            .extension()
+            .and_then(OsStr::to_str)?
+            .to_lowercase()
+            .as_str()
+        {
+            "vtt" => Some(Self::WebVtt),
+            "srt" => Some(Self::SubRip),
+            "ass" | "ssa" => Some(Self::Ass),
+            _ => None,
+        }
+    }
+}
+
+/// A subtitle file found next to a video, e.g. `movie.en.srt` for `movie.mp4`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Subtitle {
+    /// The language part of the file name, or `und` when there is none.
+    pub lang: String,
+    pub path: String,
+    pub format: SubtitleFormat,
+}
+
+impl Subtitle {
+    /// Finds the sidecar subtitles of a video, one per language, preferring WebVTT over SubRip
+    /// over ASS when a language comes in several formats.
+    pub fn discover(path: impl AsRef<Path>) -> Vec<Self> {
+        let path = path.as_ref();
+        let (Some(directory), Some(stem)) = (path.parent(), path.file_stem()) else {
+            return Vec::new();
+        };
+        let Some(stem) = stem.to_str() else {
+            return Vec::new();
+        };
+        let Ok(read_dir) = directory.read_dir() else {
+            return Vec::new();
+        };
+        let mut subtitles = read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let format = SubtitleFormat::from_path(&path)?;
+                let name = path.file_stem()?.to_str()?;
+                let lang = if name == stem {
+                    "und"
+                } else {
+                    name.strip_prefix(stem)?.strip_prefix('.')?
+                };
+                Some(Self {
+                    lang: lang.to_string(),
+                    path: path.to_str()?.to_string(),
+                    format,
+                })
+            })
+            .collect::<Vec<_>>();
+        subtitles.sort_by(|a, b| a.lang.cmp(&b.lang).then(a.format.cmp(&b.format)));
+        subtitles.dedup_by(|a, b| a.lang == b.lang);
+        subtitles
+    }
+
+    /// Converts the raw content of the subtitle file to WebVTT.
+    pub fn to_webvtt(&self, bytes: &[u8]) -> String {
+        let text = decode(bytes);
+        match self.format {
+            SubtitleFormat::WebVtt => text,
+            SubtitleFormat::SubRip => sub_rip_to_webvtt(text.as_str()),
+            SubtitleFormat::Ass => ass_to_webvtt(text.as_str()),
+        }
+    }
+}
+
+/// Decodes text of an unknown charset, e.g. legacy Shift-JIS or GBK subtitles, to UTF-8.
+fn decode(bytes: &[u8]) -> String {
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let (text, _, _) = detector.guess(None, true).decode(bytes);
+    text.replace("\r\n", "\n")
+}
+
+/// Parses `H:MM:SS.cc` or `HH:MM:SS,mmm` into milliseconds.
+fn timestamp(input: &str) -> IResult<&str, u64> {
+    let number = || map_res(digit1, str::parse::<u64>);
+    let (rest, (hours, minutes, seconds, _, fraction)) = tuple((
+        terminated(number(), tag(":")),
+        terminated(number(), tag(":")),
+        number(),
+        one_of(",."),
+        digit1,
+    ))(input)?;
+    let millis = format!("{fraction:0<3}")[..3]
+        .parse::<u64>()
+        .unwrap_or_default();
+    Ok((
+        rest,
+        ((hours * 60 + minutes) * 60 + seconds) * 1000 + millis,
+    ))
+}
+
+fn format_timestamp(millis: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+fn timing(input: &str) -> IResult<&str, (u64, u64)> {
+    let (rest, (start, _, _, _, end)) =
+        tuple((timestamp, space0, tag("-->"), space0, timestamp))(input)?;
+    Ok((rest, (start, end)))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn sub_rip_to_webvtt(text: &str) -> String {
+    let mut webvtt = String::from("WEBVTT\n\n");
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        match timing(line.trim()) {
+            Ok((rest, (start, end))) => {
+                webvtt.push_str(
+                    format!(
+                        "{} --> {}{rest}",
+                        format_timestamp(start),
+                        format_timestamp(end)
+                    )
+                    .as_str(),
+                );
+            }
+            Err(_) => webvtt.push_str(line),
+        }
+        webvtt.push('\n');
+    }
+    webvtt
+}
+
+/// Keeps the dialogue of an ASS/SSA script, dropping styles and override tags that WebVTT
+/// cannot express.
+fn ass_to_webvtt(text: &str) -> String {
+    let mut in_events = false;
+    let mut format = [
+        "layer", "start", "end", "style", "name", "marginl", "marginr", "marginv", "effect", "text",
+    ]
+    .map(ToString::to_string)
+    .to_vec();
+    let mut cues = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[events]");
+        } else if !in_events {
+            continue;
+        } else if let Some(fields) = line.strip_prefix("Format:") {
+            format = fields
+                .split(',')
+                .map(|field| field.trim().to_lowercase())
+                .collect();
+        } else if let Some(fields) = line.strip_prefix("Dialogue:") {
+            let fields = fields.splitn(format.len(), ',').collect::<Vec<_>>();
+            let field = |name: &str| {
+                format
+                    .iter()
+                    .position(|field| field == name)
+                    .and_then(|index| fields.get(index))
+                    .map(|field| field.trim())
+            };
+            let (Some(Ok((_, start))), Some(Ok((_, end))), Some(text)) = (
+                field("start").map(timestamp),
+                field("end").map(timestamp),
+                field("text"),
+            ) else {
+                continue;
+            };
+            cues.push((start, end, ass_text(text)));
+        }
+    }
+    cues.sort_by_key(|(start, _, _)| *start);
+    cues.into_iter().fold(
+        String::from("WEBVTT\n\n"),
+        |mut webvtt, (start, end, text)| {
+            webvtt.push_str(
+                format!(
+                    "{} --> {}\n{text}\n\n",
+                    format_timestamp(start),
+                    format_timestamp(end)
+                )
+                .as_str(),
+            );
+            webvtt
+        },
+    )
+}
+
+fn ass_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_override = false;
+    for c in text.chars() {
+        match c {
+            '{' => in_override = true,
+            '}' => in_override = false,
+            _ if !in_override => plain.push(c),
+            _ => {}
+        }
+    }
+    escape(plain.as_str())
+        .replace("\\N", "\n")
+        .replace("\\n", "\n")
+        .replace("\\h", " ")
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test::fixtures_dir;
+
+    fn subtitle(lang: &str) -> Subtitle {
+        Subtitle::discover(fixtures_dir().join("c/a/2.mp4"))
+            .into_iter()
+            .find(|subtitle| subtitle.lang == lang)
+            .unwrap()
+    }
+
+    #[test]
+    fn discover() {
+        let subtitles = Subtitle::discover(fixtures_dir().join("c/a/2.mp4"));
+        assert_eq!(
+            subtitles
+                .iter()
+                .map(|subtitle| (subtitle.lang.as_str(), subtitle.format))
+                .collect::<Vec<_>>(),
+            vec![("en", SubtitleFormat::SubRip), ("ja", SubtitleFormat::Ass)]
+        );
+        assert!(Subtitle::discover(fixtures_dir().join("a/a/a/1.mp4")).is_empty());
+    }
+
+    #[test]
+    fn sub_rip() {
+        let subtitle = subtitle("en");
+        let webvtt = subtitle.to_webvtt(std::fs::read(&subtitle.path).unwrap().as_slice());
+        assert_eq!(
+            webvtt,
+            "WEBVTT\n\n1\n00:00:01.000 --> 00:00:04.500\nHello, world!\n\n2\n00:00:05.000 --> 00:00:07.250\nSecond line\nwith a break\n"
+        );
+    }
+
+    #[test]
+    fn ass() {
+        let subtitle = subtitle("ja");
+        let webvtt = subtitle.to_webvtt(std::fs::read(&subtitle.path).unwrap().as_slice());
+        assert_eq!(
+            webvtt,
+            "WEBVTT\n\n00:00:01.000 --> 00:00:04.500\nこんにちは、世界！\nこれは字幕のテストです。\n\n00:00:05.000 --> 00:00:07.250\n二行目の台詞、句読点付き。\n\n"
+        );
+    }
+}
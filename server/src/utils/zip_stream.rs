@@ -0,0 +1,297 @@
+use std::io::Write;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use crc32fast::Hasher;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const ZIP64_END_SIGNATURE: u32 = 0x06064b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+const END_SIGNATURE: u32 = 0x06054b50;
+
+const VERSION: u16 = 20;
+const VERSION_ZIP64: u16 = 45;
+/// Made by a Unix host, so that the external attributes carry file permissions.
+const VERSION_MADE_BY: u16 = 3 << 8 | VERSION_ZIP64;
+/// Sizes and CRC follow the content in a data descriptor, and names are UTF-8.
+const FLAGS: u16 = 1 << 3 | 1 << 11;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+/// `-rw-r--r--` regular file.
+const EXTERNAL_ATTRIBUTES: u32 = 0o100644 << 16;
+
+struct Record {
+    name: String,
+    time: u16,
+    date: u16,
+    crc: u32,
+    size: u64,
+    offset: u64,
+}
+
+impl Record {
+    fn zip64(&self) -> bool {
+        self.size >= u32::MAX as u64 || self.offset >= u32::MAX as u64
+    }
+}
+
+/// Writes a ZIP archive front to back, without ever seeking, so that it can be streamed while it
+/// is being produced. Members are stored uncompressed, and ZIP64 records are only written when
+/// sizes, offsets or counts overflow the classic format.
+pub struct ZipStream<W: Write> {
+    writer: W,
+    offset: u64,
+    records: Vec<Record>,
+}
+
+impl<W: Write> ZipStream<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            offset: 0,
+            records: Vec::new(),
+        }
+    }
+
+    /// Adds a member of `size` bytes, whose content is written by `content`.
+    pub fn add(
+        &mut self,
+        name: &str,
+        modified: DateTime<Utc>,
+        size: u64,
+        content: impl FnOnce(&mut dyn Write) -> Result<()>,
+    ) -> Result<()> {
+        let (time, date) = dos_date_time(modified);
+        let zip64 = size >= u32::MAX as u64;
+        let mut header = Vec::with_capacity(30 + name.len() + 20);
+        put_u32(&mut header, LOCAL_HEADER_SIGNATURE);
+        put_u16(&mut header, if zip64 { VERSION_ZIP64 } else { VERSION });
+        put_u16(&mut header, FLAGS);
+        put_u16(&mut header, 0);
+        put_u16(&mut header, time);
+        put_u16(&mut header, date);
+        put_u32(&mut header, 0);
+        put_u32(&mut header, if zip64 { u32::MAX } else { 0 });
+        put_u32(&mut header, if zip64 { u32::MAX } else { 0 });
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, if zip64 { 20 } else { 0 });
+        header.extend_from_slice(name.as_bytes());
+        if zip64 {
+            put_u16(&mut header, ZIP64_EXTRA_ID);
+            put_u16(&mut header, 16);
+            put_u64(&mut header, 0);
+            put_u64(&mut header, 0);
+        }
+        self.write(&header)?;
+
+        let mut writer = ContentWriter {
+            writer: &mut self.writer,
+            hasher: Hasher::new(),
+            written: 0,
+        };
+        let result = content(&mut writer);
+        let (crc, written) = (writer.hasher.finalize(), writer.written);
+        self.offset += written;
+        result?;
+        if written != size {
+            return Err(anyhow!(
+                "{name} changed while archiving: expected {size} bytes, got {written}"
+            ));
+        }
+
+        let mut descriptor = Vec::with_capacity(24);
+        put_u32(&mut descriptor, DATA_DESCRIPTOR_SIGNATURE);
+        put_u32(&mut descriptor, crc);
+        if zip64 {
+            put_u64(&mut descriptor, size);
+            put_u64(&mut descriptor, size);
+        } else {
+            put_u32(&mut descriptor, size as u32);
+            put_u32(&mut descriptor, size as u32);
+        }
+        let offset = self.offset - size - header.len() as u64;
+        self.write(&descriptor)?;
+
+        self.records.push(Record {
+            name: name.to_string(),
+            time,
+            date,
+            crc,
+            size,
+            offset,
+        });
+        Ok(())
+    }
+
+    /// Writes the central directory and hands the writer back.
+    pub fn finish(mut self) -> Result<W> {
+        let start = self.offset;
+        for header in self.central_headers() {
+            self.write(&header)?;
+        }
+        let count = self.records.len() as u64;
+        let size = self.offset - start;
+
+        let mut end = Vec::with_capacity(98);
+        if count >= u16::MAX as u64 || size >= u32::MAX as u64 || start >= u32::MAX as u64 {
+            let zip64_end = self.offset;
+            put_u32(&mut end, ZIP64_END_SIGNATURE);
+            put_u64(&mut end, 44);
+            put_u16(&mut end, VERSION_MADE_BY);
+            put_u16(&mut end, VERSION_ZIP64);
+            put_u32(&mut end, 0);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, count);
+            put_u64(&mut end, count);
+            put_u64(&mut end, size);
+            put_u64(&mut end, start);
+            put_u32(&mut end, ZIP64_LOCATOR_SIGNATURE);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, zip64_end);
+            put_u32(&mut end, 1);
+        }
+        put_u32(&mut end, END_SIGNATURE);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, count.min(u16::MAX as u64) as u16);
+        put_u16(&mut end, count.min(u16::MAX as u64) as u16);
+        put_u32(&mut end, size.min(u32::MAX as u64) as u32);
+        put_u32(&mut end, start.min(u32::MAX as u64) as u32);
+        put_u16(&mut end, 0);
+        self.write(&end)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn central_headers(&self) -> Vec<Vec<u8>> {
+        self.records
+            .iter()
+            .map(|record| {
+                let zip64 = record.zip64();
+                let mut extra = Vec::new();
+                if record.size >= u32::MAX as u64 {
+                    put_u64(&mut extra, record.size);
+                    put_u64(&mut extra, record.size);
+                }
+                if record.offset >= u32::MAX as u64 {
+                    put_u64(&mut extra, record.offset);
+                }
+                let mut header = Vec::with_capacity(46 + record.name.len() + 4 + extra.len());
+                put_u32(&mut header, CENTRAL_HEADER_SIGNATURE);
+                put_u16(&mut header, VERSION_MADE_BY);
+                put_u16(&mut header, if zip64 { VERSION_ZIP64 } else { VERSION });
+                put_u16(&mut header, FLAGS);
+                put_u16(&mut header, 0);
+                put_u16(&mut header, record.time);
+                put_u16(&mut header, record.date);
+                put_u32(&mut header, record.crc);
+                put_u32(&mut header, record.size.min(u32::MAX as u64) as u32);
+                put_u32(&mut header, record.size.min(u32::MAX as u64) as u32);
+                put_u16(&mut header, record.name.len() as u16);
+                put_u16(&mut header, if zip64 { 4 + extra.len() as u16 } else { 0 });
+                put_u16(&mut header, 0);
+                put_u16(&mut header, 0);
+                put_u16(&mut header, 0);
+                put_u32(&mut header, EXTERNAL_ATTRIBUTES);
+                put_u32(&mut header, record.offset.min(u32::MAX as u64) as u32);
+                header.extend_from_slice(record.name.as_bytes());
+                if zip64 {
+                    put_u16(&mut header, ZIP64_EXTRA_ID);
+                    put_u16(&mut header, extra.len() as u16);
+                    header.extend_from_slice(&extra);
+                }
+                header
+            })
+            .collect()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// Passes member content through while computing its CRC and size.
+struct ContentWriter<'a, W: Write> {
+    writer: &'a mut W,
+    hasher: Hasher,
+    written: u64,
+}
+
+impl<W: Write> Write for ContentWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.writer.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        self.written += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// MS-DOS time and date, clamped to the 1980 to 2107 range the format can represent.
+fn dos_date_time(time: DateTime<Utc>) -> (u16, u16) {
+    if time.year() < 1980 {
+        return (0, 1 << 5 | 1);
+    }
+    if time.year() > 2107 {
+        return (23 << 11 | 59 << 5 | 29, 127 << 9 | 12 << 5 | 31);
+    }
+    (
+        (time.hour() << 11 | time.minute() << 5 | (time.second() / 2)) as u16,
+        ((time.year() as u32 - 1980) << 9 | time.month() << 5 | time.day()) as u16,
+    )
+}
+
+fn put_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use chrono::TimeZone;
+    use zip::ZipArchive;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let modified = Utc.with_ymd_and_hms(2024, 5, 6, 7, 8, 10).unwrap();
+        let mut zip = ZipStream::new(Vec::new());
+        zip.add("a.txt", modified, 5, |writer| {
+            Ok(writer.write_all(b"hello")?)
+        })
+        .unwrap();
+        zip.add("b/ç.bin", modified, 0, |_| Ok(())).unwrap();
+        assert!(zip
+            .add("c.txt", modified, 2, |writer| Ok(writer.write_all(b"x")?))
+            .is_err());
+        let bytes = zip.finish().unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.len(), 2);
+        let mut member = archive.by_index(0).unwrap();
+        assert_eq!(member.name(), "a.txt");
+        let time = member.last_modified().unwrap();
+        assert_eq!((time.year(), time.hour(), time.second()), (2024, 7, 10));
+        let mut content = String::new();
+        member.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello");
+        drop(member);
+        assert_eq!(archive.by_index(1).unwrap().name(), "b/ç.bin");
+    }
+}